@@ -0,0 +1,268 @@
+use crate::type_defs::PatternId;
+use std::collections::{HashMap, VecDeque};
+
+const ROOT: usize = 0;
+
+/// Which match wins among those starting at the leftmost position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchKind {
+    /// The pattern listed first, like an alternation tried left to right
+    LeftmostFirst,
+    /// The longest pattern
+    LeftmostLongest,
+}
+
+/// Occurrence of a pattern, with bounds in characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Match {
+    pub pattern: PatternId,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// Trie of literal patterns with failure links, which finds all of them in a
+/// single pass over the text.
+///
+/// The failure link of a node points to the longest proper suffix of its
+/// string that is also in the trie. Optionally every failure chain is resolved
+/// ahead of time, which turns the trie into a DFA over the pattern characters.
+#[derive(Clone, Debug)]
+pub struct AhoCorasick {
+    nodes: Vec<TrieNode>,
+    // Length in characters of every pattern
+    lengths: Vec<usize>,
+    kind: MatchKind,
+    // Complete transitions of every node, once the trie is DFA-ified
+    dfa: Option<Vec<HashMap<char, usize>>>,
+}
+
+#[derive(Clone, Debug, Default)]
+struct TrieNode {
+    transitions: HashMap<char, usize>,
+    fail: usize,
+    // Patterns ending here, including those reached through failure links
+    outputs: Vec<PatternId>,
+}
+
+impl AhoCorasick {
+    pub fn new(patterns: &[&str], kind: MatchKind) -> Self {
+        let mut automaton = AhoCorasick {
+            nodes: vec![TrieNode::default()],
+            lengths: patterns
+                .iter()
+                .map(|pattern| pattern.chars().count())
+                .collect(),
+            kind,
+            dfa: None,
+        };
+        for (pattern, literal) in patterns.iter().enumerate() {
+            automaton.insert(pattern, literal);
+        }
+        automaton.link();
+        automaton
+    }
+
+    /// Same automaton, with the failure links resolved into full transitions.
+    pub fn with_dfa(patterns: &[&str], kind: MatchKind) -> Self {
+        let mut automaton = Self::new(patterns, kind);
+        let mut dfa: Vec<HashMap<char, usize>> = vec![HashMap::new(); automaton.nodes.len()];
+        // Parents come first in breadth first order, so their rows are complete
+        for node in automaton.breadth_first() {
+            let mut row = match node {
+                ROOT => HashMap::new(),
+                _ => dfa[automaton.nodes[node].fail].clone(),
+            };
+            row.extend(automaton.nodes[node].transitions.iter());
+            dfa[node] = row;
+        }
+        automaton.dfa = Some(dfa);
+        automaton
+    }
+
+    fn insert(&mut self, pattern: PatternId, literal: &str) {
+        let mut node = ROOT;
+        for token in literal.chars() {
+            let next_id = self.nodes.len();
+            node = match self.nodes[node].transitions.get(&token) {
+                Some(next) => *next,
+                None => {
+                    self.nodes[node].transitions.insert(token, next_id);
+                    self.nodes.push(TrieNode::default());
+                    next_id
+                }
+            };
+        }
+        self.nodes[node].outputs.push(pattern);
+    }
+
+    // Failure links of a node only depend on shallower nodes
+    fn link(&mut self) {
+        for node in self.breadth_first() {
+            let children: Vec<(char, usize)> = self.nodes[node]
+                .transitions
+                .iter()
+                .map(|(token, child)| (*token, *child))
+                .collect();
+            for (token, child) in children {
+                let fail = match node {
+                    ROOT => ROOT,
+                    _ => self.follow(self.nodes[node].fail, token),
+                };
+                self.nodes[child].fail = fail;
+                let inherited = self.nodes[fail].outputs.clone();
+                let outputs = &mut self.nodes[child].outputs;
+                outputs.extend(inherited);
+                outputs.sort();
+                outputs.dedup();
+            }
+        }
+    }
+
+    fn breadth_first(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = Vec::with_capacity(self.nodes.len());
+        let mut queue: VecDeque<usize> = VecDeque::from([ROOT]);
+        while let Some(node) = queue.pop_front() {
+            order.push(node);
+            let mut children: Vec<usize> = self.nodes[node].transitions.values().cloned().collect();
+            children.sort();
+            queue.extend(children);
+        }
+        order
+    }
+
+    // Next node after reading `token`, falling back along failure links
+    fn follow(&self, mut node: usize, token: char) -> usize {
+        if let Some(dfa) = &self.dfa {
+            return dfa[node].get(&token).cloned().unwrap_or(ROOT);
+        }
+        loop {
+            if let Some(next) = self.nodes[node].transitions.get(&token) {
+                return *next;
+            }
+            if node == ROOT {
+                return ROOT;
+            }
+            node = self.nodes[node].fail;
+        }
+    }
+
+    /// Every occurrence of every pattern, ordered by end then by pattern.
+    pub fn find_overlapping(&self, text: &str) -> Vec<Match> {
+        let mut matches: Vec<Match> = Vec::new();
+        self.scan(text, 0, 0, |end, outputs| {
+            matches.extend(outputs.iter().map(|pattern| self.match_of(*pattern, end)));
+            true
+        });
+        matches
+    }
+
+    /// Leftmost match starting at character `from` or later, chosen by the kind.
+    pub fn find_at(&self, text: &str, from: usize) -> Option<Match> {
+        let offset = text
+            .char_indices()
+            .nth(from)
+            .map_or(text.len(), |(offset, _)| offset);
+        self.find_from(text, from, offset)
+    }
+
+    // Same as `find_at`, with `offset` the byte offset of character `from`
+    fn find_from(&self, text: &str, from: usize, offset: usize) -> Option<Match> {
+        let longest = self.lengths.iter().max().cloned().unwrap_or(0);
+        let mut best: Option<Match> = None;
+        self.scan(text, from, offset, |end, outputs| {
+            for pattern in outputs {
+                let found = self.match_of(*pattern, end);
+                let better = match best {
+                    None => true,
+                    Some(best) if found.start != best.start => found.start < best.start,
+                    Some(best) => match self.kind {
+                        MatchKind::LeftmostFirst => found.pattern < best.pattern,
+                        MatchKind::LeftmostLongest => found.end > best.end,
+                    },
+                };
+                if better {
+                    best = Some(found);
+                }
+            }
+            // Later matches cannot start at or before the best one any more
+            best.is_none_or(|best| end < best.start + longest)
+        });
+        best
+    }
+
+    pub fn find(&self, text: &str) -> Option<Match> {
+        self.find_at(text, 0)
+    }
+
+    /// Successive non-overlapping leftmost matches.
+    pub fn find_iter(&self, text: &str) -> Vec<Match> {
+        let mut matches: Vec<Match> = Vec::new();
+        let length = text.chars().count();
+        let mut from = 0;
+        // Byte offset of character `from`, carried along so that no search
+        // has to skip the text before it again
+        let mut offset = 0;
+        while from <= length {
+            let Some(found) = self.find_from(text, from, offset) else {
+                break;
+            };
+            // An empty pattern would otherwise be found again at the same place
+            let next = found.end.max(found.start + 1);
+            offset += text[offset..]
+                .chars()
+                .take(next - from)
+                .map(char::len_utf8)
+                .sum::<usize>();
+            from = next;
+            matches.push(found);
+        }
+        matches
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        let mut found = false;
+        self.scan(text, 0, 0, |_, outputs| {
+            found = !outputs.is_empty();
+            !found
+        });
+        found
+    }
+
+    // Hands the patterns ending at every position from character `from`,
+    // found at byte `offset`, to `report` until it returns false
+    fn scan(
+        &self,
+        text: &str,
+        from: usize,
+        offset: usize,
+        mut report: impl FnMut(usize, &[PatternId]) -> bool,
+    ) {
+        let mut node = ROOT;
+        // Empty patterns already end at `from`, before any character is read
+        if !report(from, &self.nodes[node].outputs) {
+            return;
+        }
+        for (pos, token) in text[offset..].chars().enumerate() {
+            node = self.follow(node, token);
+            if !report(from + pos + 1, &self.nodes[node].outputs) {
+                return;
+            }
+        }
+    }
+
+    fn match_of(&self, pattern: PatternId, end: usize) -> Match {
+        Match {
+            pattern,
+            start: end - self.lengths[pattern],
+            end,
+        }
+    }
+
+    pub fn pattern_count(&self) -> usize {
+        self.lengths.len()
+    }
+
+    pub fn kind(&self) -> MatchKind {
+        self.kind
+    }
+}
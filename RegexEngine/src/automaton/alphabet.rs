@@ -0,0 +1,125 @@
+use crate::{automaton::dfa::DFA, tree::Node, type_defs::State};
+use std::collections::HashMap;
+
+pub type ClassId = u32;
+
+/// Partition of all characters into classes that every transition treats the
+/// same way. Class 0 holds every character no transition mentions.
+#[derive(Clone, Debug)]
+pub struct CharClasses {
+    ascii: [ClassId; 128],
+    // Non-ASCII members of the explicit classes, sorted by character
+    other: Vec<(char, ClassId)>,
+    // Explicit members of every class. Class 0 also covers everything not listed
+    members: Vec<Vec<char>>,
+}
+
+impl CharClasses {
+    /// One class per character of `alphabet`, plus class 0.
+    pub fn from_alphabet(alphabet: &[char]) -> Self {
+        let mut alphabet = alphabet.to_vec();
+        alphabet.sort();
+        alphabet.dedup();
+        let mut members = vec![Vec::new()];
+        members.extend(alphabet.into_iter().map(|token| vec![token]));
+        Self::from_members(members)
+    }
+
+    /// Boundaries of the partition come from the literals of the pattern:
+    /// each one gets its own class and everything in between shares class 0.
+    pub fn from_ast(tree: &Node<char>) -> Self {
+        let mut literals: Vec<char> = Vec::new();
+        collect_literals(tree, &mut literals);
+        Self::from_alphabet(&literals)
+    }
+
+    fn from_members(members: Vec<Vec<char>>) -> Self {
+        let mut classes = CharClasses {
+            ascii: [0; 128],
+            other: Vec::new(),
+            members,
+        };
+        for (class, tokens) in classes.members.iter().enumerate() {
+            for token in tokens {
+                if token.is_ascii() {
+                    classes.ascii[*token as usize] = class as ClassId;
+                } else {
+                    classes.other.push((*token, class as ClassId));
+                }
+            }
+        }
+        classes.other.sort();
+        classes
+    }
+
+    /// Merges the classes whose columns are identical in `dfa`. A class whose
+    /// characters behave like unlisted ones, following the default
+    /// transitions or leading nowhere, joins class 0.
+    pub fn merge_equivalent(&self, dfa: &DFA<char>) -> Self {
+        let mut states: Vec<State> = dfa.automaton.keys().cloned().collect();
+        states.sort();
+
+        let default_column: Vec<Option<State>> = states
+            .iter()
+            .map(|state| dfa.others.get(state).cloned())
+            .collect();
+        let mut columns: HashMap<Vec<Option<State>>, usize> = HashMap::new();
+        columns.insert(default_column, 0);
+        let mut members: Vec<Vec<char>> = vec![self.members[0].clone()];
+
+        for tokens in self.members.iter().skip(1) {
+            let column: Vec<Option<State>> = states
+                .iter()
+                .map(|state| dfa.next_state(*state, &tokens[0]))
+                .collect();
+            let next_class = members.len();
+            let class = *columns.entry(column).or_insert(next_class);
+            if class == next_class {
+                members.push(Vec::new());
+            }
+            members[class].extend(tokens.iter());
+        }
+        Self::from_members(members)
+    }
+
+    #[inline]
+    pub fn get(&self, token: char) -> usize {
+        if token.is_ascii() {
+            return self.ascii[token as usize] as usize;
+        }
+        match self
+            .other
+            .binary_search_by_key(&token, |(member, _)| *member)
+        {
+            Ok(position) => self.other[position].1 as usize,
+            Err(_) => 0,
+        }
+    }
+
+    /// Explicit characters of `class`.
+    pub fn members(&self, class: usize) -> &[char] {
+        &self.members[class]
+    }
+
+    /// Number of classes, including class 0.
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+}
+
+fn collect_literals(node: &Node<char>, literals: &mut Vec<char>) {
+    if node.is_epsilon() {
+        return;
+    }
+    if node.is_leaf() {
+        literals.push(node.value);
+        return;
+    }
+    for child in [&node.left, &node.right].into_iter().flatten() {
+        collect_literals(child, literals);
+    }
+}
@@ -0,0 +1,104 @@
+use crate::automaton::pikevm::PikeVM;
+use crate::thompson::{Inst, InstPtr, Program, Slots};
+
+/// Default size of the visited bitset, in bits
+pub const DEFAULT_VISITED_CAPACITY: usize = 256 * 1024 * 8;
+
+/// Runs the Thompson program depth first, trying higher priority branches first.
+///
+/// Every (instruction, position) pair is explored at most once: reaching it
+/// again can only fail the same way. That bounds a search by `m·n` steps, but
+/// the bitset is that large too, so only short texts are worth it.
+#[derive(Clone, Debug)]
+pub struct Backtracker {
+    program: Program,
+    capacity: usize,
+}
+
+enum Job {
+    Explore(InstPtr, usize),
+    // Puts back the value a slot had before a Save overwrote it
+    Restore(usize, Option<usize>),
+}
+
+impl Backtracker {
+    pub fn new(program: &Program) -> Self {
+        Self::with_capacity(program, DEFAULT_VISITED_CAPACITY)
+    }
+
+    pub fn with_capacity(program: &Program, capacity: usize) -> Self {
+        Backtracker {
+            program: program.clone(),
+            capacity,
+        }
+    }
+
+    /// Longest text, in characters, whose visited bitset fits the capacity.
+    pub fn max_haystack_len(&self) -> usize {
+        (self.capacity / self.program.insts.len()).saturating_sub(1)
+    }
+
+    /// Capture slots of the leftmost-first match. A text longer than
+    /// `max_haystack_len` is run on the Pike VM instead, whose memory does not
+    /// grow with it.
+    pub fn captures(&self, text: &str) -> Option<Slots> {
+        let chars: Vec<char> = text.chars().collect();
+        if chars.len() > self.max_haystack_len() {
+            return PikeVM::new(&self.program).captures(text);
+        }
+        let width = chars.len() + 1;
+        let bits = self.program.insts.len() * width;
+        // Failures are shared between starts, since they do not depend on it
+        let mut visited: Vec<u64> = vec![0; bits.div_ceil(64)];
+        for start in 0..width {
+            if let Some(slots) = self.run(&chars, start, &mut visited) {
+                return Some(slots);
+            }
+        }
+        return None;
+    }
+
+    fn run(&self, chars: &[char], start: usize, visited: &mut [u64]) -> Option<Slots> {
+        let width = chars.len() + 1;
+        let mut slots: Slots = vec![None; self.program.slots];
+        let mut stack: Vec<Job> = vec![Job::Explore(self.program.start, start)];
+
+        while let Some(job) = stack.pop() {
+            let (mut pc, mut pos) = match job {
+                Job::Explore(pc, pos) => (pc, pos),
+                Job::Restore(slot, old) => {
+                    slots[slot] = old;
+                    continue;
+                }
+            };
+            loop {
+                let bit = pc * width + pos;
+                if visited[bit / 64] & (1 << (bit % 64)) != 0 {
+                    break;
+                }
+                visited[bit / 64] |= 1 << (bit % 64);
+
+                match self.program.insts[pc] {
+                    Inst::Char(ch, target) => {
+                        if chars.get(pos) != Some(&ch) {
+                            break;
+                        }
+                        pc = target;
+                        pos += 1;
+                    }
+                    Inst::Split(first, second) => {
+                        stack.push(Job::Explore(second, pos));
+                        pc = first;
+                    }
+                    Inst::Save(slot, target) => {
+                        stack.push(Job::Restore(slot, slots[slot]));
+                        slots[slot] = Some(pos);
+                        pc = target;
+                    }
+                    Inst::Match => return Some(slots),
+                }
+            }
+        }
+        return None;
+    }
+}
@@ -0,0 +1,329 @@
+use crate::{
+    automaton::{alphabet::CharClasses, dfa::DFA},
+    haystack::Haystack,
+    type_defs::{PatternId, State},
+};
+use std::collections::{HashMap, VecDeque};
+
+pub type StateId = u32;
+
+/// Every missing transition leads here. Its row loops back onto itself.
+pub const DEAD: StateId = 0;
+
+/// Compiled form of a `DFA<char>`: one contiguous row of `stride` state ids per
+/// state, indexed by character class.
+///
+/// Ids are laid out so that special states are recognised by comparison alone:
+/// `DEAD` is 0, non-final states come next and final states are `min_match..`.
+#[derive(Clone, Debug)]
+pub struct DenseDFA {
+    pub(crate) table: Vec<StateId>,
+    pub(crate) stride: usize,
+    pub(crate) classes: CharClasses,
+    pub(crate) start: StateId,
+    pub(crate) min_match: StateId,
+    // Patterns accepted by every final state, indexed from `min_match`
+    pub(crate) patterns: Vec<Vec<PatternId>>,
+}
+
+impl From<&DFA<char>> for DenseDFA {
+    fn from(dfa: &DFA<char>) -> Self {
+        let alphabet: Vec<char> = dfa
+            .automaton
+            .values()
+            .flat_map(|transitions| transitions.keys())
+            .cloned()
+            .collect();
+        let classes = CharClasses::from_alphabet(&alphabet).merge_equivalent(dfa);
+        DenseDFA::new(dfa, classes)
+    }
+}
+
+impl DenseDFA {
+    /// Lays `dfa` out with one column per class. Characters sharing a class
+    /// must have the same transitions in `dfa`.
+    pub fn new(dfa: &DFA<char>, classes: CharClasses) -> Self {
+        let stride = classes.len();
+
+        let end = dfa.end.clone().unwrap_or_default();
+        let mut states: Vec<State> = dfa.automaton.keys().cloned().collect();
+        // Final states go last so that they occupy the highest ids
+        states.sort_by_key(|state| (end.contains_key(state), *state));
+        let ids: HashMap<State, StateId> = states
+            .iter()
+            .enumerate()
+            .map(|(position, state)| (*state, position as StateId + 1))
+            .collect();
+        let min_match = states
+            .iter()
+            .filter(|state| !end.contains_key(state))
+            .count() as StateId
+            + 1;
+        let patterns = states
+            .iter()
+            .filter_map(|state| end.get(state).cloned())
+            .collect();
+
+        let mut table: Vec<StateId> = vec![DEAD; (states.len() + 1) * stride];
+        for state in states.iter() {
+            let row = ids[state] as usize * stride;
+            if let Some(destination) = dfa.others.get(state) {
+                table[row..row + stride].fill(ids[destination]);
+            }
+            for (token, destination) in dfa.automaton.get(state).unwrap() {
+                table[row + classes.get(*token)] = ids[destination];
+            }
+        }
+
+        DenseDFA {
+            table,
+            stride,
+            classes,
+            start: ids[&dfa.start.unwrap()],
+            min_match,
+            patterns,
+        }
+    }
+
+    #[inline]
+    pub fn next_state(&self, state: StateId, token: char) -> StateId {
+        self.table[state as usize * self.stride + self.classes.get(token)]
+    }
+
+    // Invalid bytes of a haystack belong to class 0, like every character no
+    // transition mentions
+    #[inline]
+    fn next_symbol(&self, state: StateId, token: Option<char>) -> StateId {
+        let class = token.map_or(0, |token| self.classes.get(token));
+        self.table[state as usize * self.stride + class]
+    }
+
+    #[inline]
+    pub fn is_match(&self, state: StateId) -> bool {
+        state >= self.min_match
+    }
+
+    /// Patterns accepted in `state`, none unless it is final.
+    pub fn patterns_of(&self, state: StateId) -> &[PatternId] {
+        if !self.is_match(state) {
+            return &[];
+        }
+        &self.patterns[(state - self.min_match) as usize]
+    }
+
+    pub fn state_count(&self) -> usize {
+        self.table.len() / self.stride
+    }
+
+    /// Position, in characters, of the leftmost match start.
+    pub fn parse(&self, text: &str) -> Option<usize> {
+        let starts = text.char_indices().map(|(offset, _)| offset);
+        for (pos, offset) in starts.chain([text.len()]).enumerate() {
+            if self.matches_at(&text[offset..]) {
+                return Some(pos);
+            }
+        }
+        return None;
+    }
+
+    /// Position, in characters, right after the earliest match end. Only
+    /// meaningful on an automaton built by `unanchored`, where a single pass
+    /// over `haystack` sees every start at once.
+    pub fn earliest_end<H: Haystack + ?Sized>(&self, haystack: &H) -> Option<usize> {
+        let mut state = self.start;
+        if self.is_match(state) {
+            return Some(0);
+        }
+        let mut offset = 0;
+        let mut pos = 0;
+        while let Some((token, width)) = haystack.char_at(offset) {
+            offset += width;
+            pos += 1;
+            state = self.next_symbol(state, token);
+            if self.is_match(state) {
+                return Some(pos);
+            }
+        }
+        return None;
+    }
+
+    /// Byte offset right after the last match of the runs alive at the
+    /// earliest match end. Only meaningful on an automaton built by
+    /// `unanchored_leftmost`: the leftmost match is one of those runs, so it
+    /// ends at or before that offset.
+    pub fn leftmost_end<H: Haystack + ?Sized>(&self, haystack: &H) -> Option<usize> {
+        let mut state = self.start;
+        let mut end = None;
+        if self.is_match(state) {
+            end = Some(0);
+        }
+        let mut offset = 0;
+        while let Some((token, width)) = haystack.char_at(offset) {
+            offset += width;
+            state = self.next_symbol(state, token);
+            if state == DEAD {
+                break;
+            }
+            if self.is_match(state) {
+                end = Some(offset);
+            }
+        }
+        return end;
+    }
+
+    /// Byte offset of the leftmost start of a match ending at or before `end`.
+    /// Only meaningful on the unanchored automaton of the reversed pattern,
+    /// which is run from `end` back to the beginning of `haystack`: every final
+    /// state it passes through marks a position where a match starts.
+    pub fn rfind_start<H: Haystack + ?Sized>(&self, haystack: &H, end: usize) -> Option<usize> {
+        let mut offset = end;
+        let mut state = self.start;
+        let mut leftmost = None;
+        if self.is_match(state) {
+            leftmost = Some(offset);
+        }
+        while let Some((token, width)) = haystack.char_before(offset) {
+            offset -= width;
+            state = self.next_symbol(state, token);
+            if self.is_match(state) {
+                leftmost = Some(offset);
+            }
+        }
+        return leftmost;
+    }
+
+    /// Builds the automaton of `.*?` followed by this one, so that a match may
+    /// start anywhere. Each of its states is the set of anchored states still
+    /// alive, the start state always being one of them.
+    ///
+    /// There can be exponentially many such sets, so None is returned as soon
+    /// as the table would need more than `limit` states.
+    pub fn unanchored(&self, limit: usize) -> Option<DenseDFA> {
+        self.unanchored_from(false, limit)
+    }
+
+    /// Same as `unanchored`, except that no run starts after the earliest
+    /// match end. The runs still alive there can only die, so a search for the
+    /// leftmost match stops as soon as the automaton does.
+    pub fn unanchored_leftmost(&self, limit: usize) -> Option<DenseDFA> {
+        self.unanchored_from(true, limit)
+    }
+
+    // States are sets of anchored states, with whether the start state is
+    // still added to their successors
+    fn unanchored_from(&self, stops_at_match: bool, limit: usize) -> Option<DenseDFA> {
+        type Set = (Vec<StateId>, bool);
+        let stride = self.stride;
+        let mut sets: Vec<Set> = vec![(vec![self.start], true)];
+        let mut ids: HashMap<Set, usize> = HashMap::new();
+        ids.insert(sets[0].clone(), 0);
+        // None for the dead state, once no run is left
+        let mut rows: Vec<Vec<Option<usize>>> = Vec::new();
+
+        let mut queue: VecDeque<usize> = VecDeque::from([0]);
+        while let Some(current) = queue.pop_front() {
+            let (states, restarts) = &sets[current];
+            let restarts =
+                *restarts && !(stops_at_match && states.iter().any(|state| self.is_match(*state)));
+            let mut row = Vec::with_capacity(stride);
+            for class in 0..stride {
+                let mut next: Vec<StateId> = sets[current]
+                    .0
+                    .iter()
+                    .map(|state| self.table[*state as usize * stride + class])
+                    .filter(|state| *state != DEAD)
+                    .collect();
+                if restarts {
+                    next.push(self.start);
+                }
+                next.sort();
+                next.dedup();
+                if next.is_empty() {
+                    row.push(None);
+                    continue;
+                }
+                let next = (next, restarts);
+                let id = match ids.get(&next) {
+                    Some(id) => *id,
+                    None => {
+                        let id = sets.len();
+                        // The dead state takes a row of its own
+                        if id + 1 >= limit {
+                            return None;
+                        }
+                        ids.insert(next.clone(), id);
+                        sets.push(next);
+                        queue.push_back(id);
+                        id
+                    }
+                };
+                row.push(Some(id));
+            }
+            // States are discovered in queue order, so `current == rows.len()`
+            rows.push(row);
+        }
+
+        // Same layout as the anchored table: dead row first, finals last
+        let labels: Vec<Vec<PatternId>> = sets
+            .iter()
+            .map(|(set, _)| {
+                let mut labels: Vec<PatternId> = set
+                    .iter()
+                    .flat_map(|state| self.patterns_of(*state).iter().cloned())
+                    .collect();
+                labels.sort();
+                labels.dedup();
+                labels
+            })
+            .collect();
+        let matching: Vec<bool> = labels.iter().map(|labels| !labels.is_empty()).collect();
+        let mut order: Vec<usize> = (0..sets.len()).collect();
+        order.sort_by_key(|set| (matching[*set], *set));
+        let mut new_ids: Vec<StateId> = vec![DEAD; sets.len()];
+        for (position, set) in order.iter().enumerate() {
+            new_ids[*set] = position as StateId + 1;
+        }
+        let min_match = matching.iter().filter(|matching| !**matching).count() as StateId + 1;
+
+        let patterns = order
+            .iter()
+            .filter(|set| matching[**set])
+            .map(|set| labels[*set].clone())
+            .collect();
+        let mut table: Vec<StateId> = vec![DEAD; (sets.len() + 1) * stride];
+        for (set, row) in rows.iter().enumerate() {
+            let offset = new_ids[set] as usize * stride;
+            for (class, destination) in row.iter().enumerate() {
+                table[offset + class] =
+                    destination.map_or(DEAD, |destination| new_ids[destination]);
+            }
+        }
+
+        Some(DenseDFA {
+            table,
+            stride,
+            classes: self.classes.clone(),
+            start: new_ids[0],
+            min_match,
+            patterns,
+        })
+    }
+
+    // Walks from the start state until a final state proves a match
+    fn matches_at(&self, text: &str) -> bool {
+        let mut state = self.start;
+        if self.is_match(state) {
+            return true;
+        }
+        for token in text.chars() {
+            state = self.next_state(state, token);
+            if state == DEAD {
+                return false;
+            }
+            if self.is_match(state) {
+                return true;
+            }
+        }
+        return false;
+    }
+}
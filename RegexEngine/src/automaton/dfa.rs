@@ -1,100 +1,120 @@
 use std::{
-    collections::{HashMap, HashSet, VecDeque},
+    collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
     fmt::Debug,
     hash::Hash,
 };
 
 use crate::{
-    automaton::efa::EFA, debug_println, state_generator::StateGenerator, type_defs::State,
+    automaton::{
+        efa::{ClosureCache, EFA},
+        nfa::NFA,
+    },
+    state_generator::StateGenerator,
+    type_defs::{PatternId, State},
 };
+pub use analysis::CHAR_COUNT;
+pub use generate::{SplitMix64, Strings};
+
+mod analysis;
+mod complete;
+mod elimination;
+mod generate;
+mod minimize;
+mod product;
+
 #[derive(Clone, Debug)]
 pub struct DFA<T> {
-    automaton: HashMap<State, HashMap<T, State>>,
-    start: Option<State>,
-    end: Option<Vec<State>>,
+    pub(crate) automaton: HashMap<State, HashMap<T, State>>,
+    pub(crate) start: Option<State>,
+    /// Final states, with the patterns they accept
+    pub(crate) end: Option<HashMap<State, Vec<PatternId>>>,
+    /// Transition taken on every symbol a state has no entry for, which lets
+    /// a finite table cover an unbounded alphabet such as all of `char`
+    pub(crate) others: HashMap<State, State>,
 }
 
 impl<T: Eq + Hash + Debug + Clone> DFA<T> {
     pub fn from_efa(efa: &EFA<T>) -> Option<Self> {
         let start = efa.get_start()?;
-        let end = efa.get_end()?;
+        Self::from_efa_starting_at(efa, &[start])
+    }
+
+    /// Same as `from_efa`, but gives up with None as soon as the subset
+    /// construction discovers more than `limit` states.
+    pub fn from_efa_bounded(efa: &EFA<T>, limit: usize) -> Option<Self> {
+        let start = efa.get_start()?;
+        Self::subset_construction(efa, &[start], limit)
+    }
+
+    /// Subset construction whose initial subset is the closure of `starts`
+    /// instead of the closure of the EFA start.
+    pub fn from_efa_starting_at(efa: &EFA<T>, starts: &[State]) -> Option<Self> {
+        Self::subset_construction(efa, starts, usize::MAX)
+    }
+
+    fn subset_construction(efa: &EFA<T>, starts: &[State], limit: usize) -> Option<Self> {
+        // Without any end state the language is empty, which is still a DFA
+        let ends = efa.pattern_ends();
 
         let mut automaton: HashMap<State, HashMap<T, State>> = HashMap::new();
-        let mut closures: HashMap<State, Vec<State>> = HashMap::new();
-        let mut end_states: HashSet<State> = HashSet::new();
+        let mut closures: ClosureCache = HashMap::new();
+        let mut end_states: HashMap<State, Vec<PatternId>> = HashMap::new();
 
         let mut queue: VecDeque<State> = VecDeque::new();
         let mut visited: HashSet<State> = HashSet::new();
 
         let mut state_generator: StateGenerator<Vec<State>, State> = StateGenerator::new();
 
-        let closure = efa.closure(start);
-        closures.insert(start, closure.clone());
+        let closure = efa.closure_of(starts.iter().cloned(), &mut closures);
         let start = state_generator.generate_for(&closure);
         queue.push_back(start);
+        visited.insert(start);
 
         while let Some(current_state) = queue.pop_front() {
             automaton.insert(current_state, HashMap::new());
             let mut new_transitions: HashMap<T, Vec<State>> = HashMap::new();
             let current_states = state_generator.get_value(&current_state).unwrap();
-            if current_states.contains(&end) {
-                end_states.insert(current_state);
+            let mut patterns: Vec<PatternId> = current_states
+                .iter()
+                .filter_map(|state| ends.get(state).cloned())
+                .collect();
+            if !patterns.is_empty() {
+                patterns.sort();
+                patterns.dedup();
+                end_states.insert(current_state, patterns);
             }
-            // For each independent state
+            // For each independent state collect where its symbols lead
             for state in current_states.iter() {
-                // Get its possible transitions
-                let transitions = efa.get_possible_transitions(&state);
+                let transitions = efa.get_possible_transitions(state);
                 if transitions.is_none() {
                     continue;
                 }
-                let transitions = transitions.unwrap();
-                let alphabet: Vec<_> = transitions.keys().collect();
-                // Using the tokens, for each individual transitions
-                for token_option in alphabet {
-                    if token_option.is_none() {
-                        continue;
+                for (token_option, destinations) in transitions.unwrap() {
+                    if let Some(token) = token_option {
+                        new_transitions
+                            .entry(token.clone())
+                            .or_default()
+                            .extend(destinations.iter());
                     }
-                    let token = token_option.clone().unwrap();
-                    // Get its destinations
-                    let destinations = transitions.get(&Some(token.clone())).unwrap();
-                    let mut new_destinations: HashSet<State> = HashSet::new();
-
-                    // Calculate the closure for each destination
-                    for destination in destinations {
-                        new_destinations.extend(
-                            closures
-                                .entry(*destination)
-                                .or_insert_with(|| {
-                                    let mut temp = efa.closure(*destination);
-                                    temp.sort();
-                                    temp
-                                })
-                                .iter(),
-                        );
-                    }
-
-                    // Generate the new possible transitions
-                    new_transitions
-                        .entry(token)
-                        .or_default()
-                        .extend(new_destinations.clone());
                 }
             }
             // For each possible transition
-            for key in new_transitions.keys() {
-                let mut value = new_transitions.get(key).unwrap().clone();
-                value.sort();
+            for (key, destinations) in new_transitions {
+                // The closure of the destinations is the key of the new state
+                let value = efa.closure_of(destinations, &mut closures);
 
                 // Check if it was visited. If not add it to the queue
-                let new_state = state_generator.generate_for(&value.clone());
-                if !visited.contains(&new_state) {
+                let new_state = state_generator.generate_for(&value);
+                if visited.insert(new_state) {
+                    if visited.len() > limit {
+                        return None;
+                    }
                     queue.push_back(new_state);
-                    visited.insert(new_state);
                 }
                 automaton
                     .get_mut(&current_state)
                     .unwrap()
-                    .entry(key.clone())
+                    .entry(key)
                     .or_insert(new_state);
             }
         }
@@ -102,180 +122,150 @@ impl<T: Eq + Hash + Debug + Clone> DFA<T> {
         Some(DFA {
             automaton,
             start: Some(start),
-            end: Some(end_states.into_iter().collect()),
+            end: Some(end_states),
+            others: HashMap::new(),
         })
     }
 
-    fn get_alphabet(&self) -> HashSet<T> {
-        self.automaton
-            .values()
-            .flat_map(|v| v.keys())
-            .cloned()
-            .collect()
-    }
-
-    fn get_transitions_with(&self, token: T) -> Vec<(State, State)> {
-        let mut result: Vec<(State, State)> = Vec::new();
-        for key in self.automaton.keys() {
-            if let Some(destination) = self.automaton.get(key).unwrap().get(&token) {
-                result.push((*key, *destination));
-            }
-        }
-        result
-    }
-
-    pub fn minimize_from(dfa: DFA<T>) -> Option<Self> {
-        let mut dfa = dfa;
-        dfa.remove_unreachable();
+    /// Subset construction over an epsilon-free automaton.
+    pub fn from_nfa(nfa: &NFA<T>) -> Option<Self> {
+        let start = nfa.get_start()?;
 
-        let q_states: HashSet<State> = dfa.automaton.keys().cloned().collect();
-        let f_vec: Vec<State> = dfa.end.clone().unwrap_or_default();
-
-        let f_states: HashSet<State> = f_vec.iter().cloned().collect();
-        let q_diff_f: HashSet<State> = &q_states - &f_states;
-
-        let mut partitions: VecDeque<HashSet<State>> = VecDeque::new();
-        let mut work_list: VecDeque<HashSet<State>> = VecDeque::new();
-
-        partitions.push_back(f_states.clone());
-        partitions.push_back(q_diff_f.clone());
-
-        work_list.push_back(f_states);
-        work_list.push_back(q_diff_f);
-
-        let alphabet = dfa.get_alphabet();
-
-        while let Some(a) = work_list.pop_front() {
-            for c in alphabet.iter() {
-                let x: HashSet<State> = dfa
-                    .get_transitions_with(c.clone())
-                    .iter()
-                    .filter(|value| a.contains(&value.1))
-                    .map(|value| value.0)
-                    .collect();
-
-                let mut to_remove: Vec<(HashSet<State>, HashSet<State>, HashSet<State>)> =
-                    Vec::new();
-                for y in partitions.iter() {
-                    let intersection: HashSet<State> = x.intersection(y).cloned().collect();
-                    let difference: HashSet<State> = y - &x;
-                    if !intersection.is_empty() && !difference.is_empty() {
-                        to_remove.push((y.clone(), intersection, difference));
-                    }
-                }
-
-                for (y, intersection, difference) in to_remove {
-                    if let Some(pos) = partitions.iter().position(|x| x == &y) {
-                        partitions.remove(pos);
-                        partitions.push_back(intersection.clone());
-                        partitions.push_back(difference.clone());
-                    }
-
-                    if let Some(pos) = work_list.iter().position(|x| x == &y) {
-                        work_list.remove(pos);
-                        work_list.push_back(intersection.clone());
-                        work_list.push_back(difference.clone());
-                    } else {
-                        if intersection.len() <= difference.len() {
-                            work_list.push_back(intersection);
-                        } else {
-                            work_list.push_back(difference);
-                        }
-                    }
-                }
-            }
-        }
-        println!("{:?} {:?}", partitions, work_list);
-        let mut state_generator: StateGenerator<Vec<State>, State> = StateGenerator::new();
-        let mut state_table: HashMap<State, State> = HashMap::new();
-
-        let mut start = dfa.start.unwrap();
-        let end = dfa.end.unwrap();
-        let mut new_end: HashSet<State> = HashSet::new();
-        println!("IS {:?} IE {:?}", start, end);
-        for partition in partitions {
-            let partition: Vec<State> = partition.iter().cloned().collect();
-            let new_state = state_generator.generate_for(&partition);
-            // if partition.contains(&start) {
-            //     start = new_state;
-            // }
-            for state in partition {
-                state_table.insert(state, new_state);
-                // if state == start {
-                //     start = new_state;
-                // }
-                if end.contains(&state) {
-                    new_end.insert(new_state);
-                }
-            }
-        }
-
-        start = *state_table.get(&start).unwrap();
-
-        println!("US {:?} UE {:?}", start, new_end);
         let mut automaton: HashMap<State, HashMap<T, State>> = HashMap::new();
+        let mut end_states: HashMap<State, Vec<PatternId>> = HashMap::new();
 
         let mut queue: VecDeque<State> = VecDeque::new();
-        let mut visited: HashSet<State> = HashSet::new();
+        let mut state_generator: StateGenerator<Vec<State>, State> = StateGenerator::new();
+
+        let start = state_generator.generate_for(&vec![start]);
         queue.push_back(start);
-        visited.insert(start);
-        println!("{:?}", state_table);
-        println!("{:?}", state_generator);
+        automaton.insert(start, HashMap::new());
+
         while let Some(current_state) = queue.pop_front() {
-            automaton.insert(current_state, HashMap::new());
-            // Linia urmatoare e problema
-            // ar trebui sa iei state ul tin tabelul construit anterior
-            let decoded_state = state_generator.get_value(&current_state).unwrap();
-            println!("CS {:?} DS {:?}", current_state, decoded_state);
-            let mut new_transitions: HashMap<T, State> = HashMap::new();
-
-            for state in decoded_state {
-                let current_transitions = dfa.automaton.get(&state).unwrap();
-                let alphabet: Vec<T> = current_transitions.keys().cloned().collect();
-                println!("ALPH {:?}", alphabet);
-                println!(
-                    "transitions {:?} {:?} {:?} {:?}",
-                    dfa.automaton.get(&state),
-                    dfa.automaton,
-                    dfa.start,
-                    state
-                );
-                for token in alphabet {
-                    let destination = current_transitions.get(&token).unwrap();
-                    let new_state = state_table.get(destination).unwrap();
-                    let entry = new_transitions.insert(token, *new_state);
-                    if let Some(temp) = entry {
-                        if temp != *new_state {
-                            panic!(
-                                "Different final states for transition {:?} with {:?}",
-                                state, entry
-                            )
-                        }
+            let current_states = state_generator.get_value(&current_state).unwrap();
+            if current_states.iter().any(|state| nfa.is_final(state)) {
+                end_states.insert(current_state, vec![0]);
+            }
+
+            let mut new_transitions: HashMap<T, Vec<State>> = HashMap::new();
+            for state in current_states.iter() {
+                if let Some(transitions) = nfa.get_possible_transitions(state) {
+                    for (token, destinations) in transitions {
+                        new_transitions
+                            .entry(token.clone())
+                            .or_default()
+                            .extend(destinations.iter());
                     }
                 }
             }
 
-            for key in new_transitions.keys() {
-                let value = new_transitions.get(key).unwrap();
-                if !visited.contains(value) {
-                    visited.insert(*value);
-                    queue.push_back(*value);
+            for (token, mut destinations) in new_transitions {
+                destinations.sort();
+                destinations.dedup();
+                let new_state = state_generator.generate_for(&destinations);
+                if let Entry::Vacant(entry) = automaton.entry(new_state) {
+                    entry.insert(HashMap::new());
+                    queue.push_back(new_state);
                 }
                 automaton
                     .get_mut(&current_state)
                     .unwrap()
-                    .entry(key.clone())
-                    .or_insert(*value);
+                    .insert(token, new_state);
             }
         }
 
         Some(DFA {
             automaton,
             start: Some(start),
-            end: Some(new_end.into_iter().collect()),
+            end: Some(end_states),
+            others: HashMap::new(),
         })
     }
 
+    /// Automaton of the reversed language: every transition is flipped, the
+    /// old start becomes the end and a fresh start reaches the old finals by epsilon.
+    ///
+    /// Default transitions cannot be flipped into an EFA, so the automaton
+    /// must not have any.
+    pub fn reverse(&self) -> EFA<T> {
+        assert!(
+            self.others.is_empty(),
+            "Cannot reverse a DFA with default transitions"
+        );
+        let mut efa: EFA<T> = EFA::new();
+        for state in self.automaton.keys() {
+            efa.empty_transition(*state);
+        }
+        for (state, transitions) in self.automaton.iter() {
+            for (token, destination) in transitions {
+                efa.transition(*destination, Some(token.clone()), *state);
+            }
+        }
+
+        let start = self.automaton.keys().max().map_or(1, |max| max + 1);
+        efa.empty_transition(start);
+        for end in self.end.iter().flat_map(|end| end.keys()) {
+            efa.transition(start, None, *end);
+        }
+        efa.set_start(start);
+        if let Some(end) = self.start {
+            efa.set_end(end);
+        }
+        efa
+    }
+
+    /// Whether the whole of `word` is in the language, from the start state.
+    pub fn accepts(&self, word: &[T]) -> bool {
+        let mut state = self.start.unwrap();
+        for token in word {
+            match self.next_state(state, token) {
+                Some(next) => state = next,
+                None => return false,
+            }
+        }
+        self.end
+            .as_ref()
+            .is_some_and(|end| end.contains_key(&state))
+    }
+
+    /// State reached from `state` on `token`, through its default transition
+    /// when it has no entry for `token`.
+    pub fn next_state(&self, state: State, token: &T) -> Option<State> {
+        if let Some(next) = self.automaton.get(&state)?.get(token) {
+            return Some(*next);
+        }
+        return self.others.get(&state).cloned();
+    }
+
+    /// Automaton of every string the original one rejects, over all symbols
+    /// and not only the ones it mentions.
+    ///
+    /// Missing transitions lead to an explicit dead state, which becomes
+    /// accepting once finality is flipped, and every state gets a default
+    /// transition for the symbols outside the alphabet. Pattern ids are not
+    /// kept: the final states of the complement accept pattern 0.
+    pub fn complement(&self) -> Self {
+        let alphabet: Vec<T> = self.get_alphabet().into_iter().collect();
+        let complete = self.complete(alphabet);
+        let labels: Vec<Vec<PatternId>> = complete
+            .labels
+            .iter()
+            .map(|label| match label.is_empty() {
+                true => vec![0],
+                false => Vec::new(),
+            })
+            .collect();
+        Self::minimize_from(complete.to_dfa(labels)).unwrap()
+    }
+
+    fn get_alphabet(&self) -> HashSet<T> {
+        self.automaton
+            .values()
+            .flat_map(|v| v.keys())
+            .cloned()
+            .collect()
+    }
+
     fn remove_unreachable(&mut self) {
         let mut reachable: HashSet<State> = HashSet::new();
         let mut new_states: HashSet<State> = HashSet::new();
@@ -287,10 +277,11 @@ impl<T: Eq + Hash + Debug + Clone> DFA<T> {
             let mut temp: HashSet<State> = HashSet::new();
 
             for state in new_states.iter() {
-                temp.extend(self.automaton.get(&state).unwrap().values().into_iter());
+                temp.extend(self.automaton.get(state).unwrap().values());
+                temp.extend(self.others.get(state));
             }
 
-            new_states = &temp - &new_states;
+            new_states = &temp - &reachable;
             reachable.extend(new_states.clone());
 
             if new_states.is_empty() {
@@ -306,11 +297,14 @@ impl<T: Eq + Hash + Debug + Clone> DFA<T> {
         }
 
         for (_, transitions) in self.automaton.iter_mut() {
-            transitions.retain(|_, target_state| !unreachable.contains(&target_state));
+            transitions.retain(|_, target_state| !unreachable.contains(target_state));
         }
+        self.others.retain(|state, target_state| {
+            !unreachable.contains(state) && !unreachable.contains(target_state)
+        });
 
         if let Some(ref mut end_states) = self.end {
-            end_states.retain(|state| !unreachable.contains(state));
+            end_states.retain(|state, _| !unreachable.contains(state));
         }
     }
 
@@ -346,10 +340,11 @@ impl<T: Eq + Hash + Debug + Clone> DFA<T> {
         for symbol in &alphabet {
             print!(" {:^8} |", format!("{:?}", symbol));
         }
+        print!(" {:^8} |", "other");
         println!();
 
         print!("{:-<width$}-|", "", width = max_state_width + 1);
-        for _ in &alphabet {
+        for _ in 0..=alphabet.len() {
             print!("-----------");
         }
         println!();
@@ -371,68 +366,48 @@ impl<T: Eq + Hash + Debug + Clone> DFA<T> {
                     print!(" {:^8} |", "-");
                 }
             }
+            match self.others.get(state) {
+                Some(target_state) => print!(" {:^8} |", format!("{:?}", target_state)),
+                None => print!(" {:^8} |", "-"),
+            }
             println!();
         }
     }
 }
 
 impl DFA<char> {
+    /// Position, in characters, of the leftmost match start.
     pub fn parse(&self, text: &str) -> Option<usize> {
-        self.print();
-        let start = self.start.unwrap();
-
-        for (pos, _) in text.chars().enumerate() {
-            debug_println!("{:?} {:?}", &text[pos..], start);
-            if self.recursive_parse(&text[pos..], start).is_some() {
+        let starts = text.char_indices().map(|(offset, _)| offset);
+        for (pos, offset) in starts.chain([text.len()]).enumerate() {
+            if self.matches_at(&text[offset..]) {
                 return Some(pos);
             }
         }
-        // Necessary for empty string that would be matched
-        // by "a*" where a is a token from the alphabet
-        if self.recursive_parse(text, start).is_some() {
-            return Some(0);
-        }
         return None;
     }
-    fn recursive_parse(&self, text: &str, state: State) -> Option<()> {
-        if self.end.clone().unwrap().contains(&state) {
-            return Some(());
-        }
 
-        let possible_transitions = self
-            .automaton
-            .get(&state)
-            .expect("There should not be empty transitions except into final state");
-        let current_token = text.chars().next()?;
-
-        let direct_transition = possible_transitions.get(&current_token);
-        if direct_transition.is_some() {
-            let direct_transition = direct_transition.unwrap();
-            // for transition in direct_transition {
-            debug_println!(
-                "  {} --{:?}--> {:2} {}",
-                state,
-                current_token,
-                direct_transition,
-                text
-            );
-
-            let result = self.recursive_parse(&text[1..], *direct_transition);
-            if result.is_some() {
-                return result;
+    /// Whether the whole of `text` is in the language.
+    pub fn is_full_match(&self, text: &str) -> bool {
+        self.accepts(&text.chars().collect::<Vec<char>>())
+    }
+
+    // Follows transitions one character at a time until a final state
+    fn matches_at(&self, text: &str) -> bool {
+        let end = self.end.as_ref();
+        let mut state = self.start.unwrap();
+        let mut tokens = text.chars();
+        loop {
+            if end.is_some_and(|end| end.contains_key(&state)) {
+                return true;
+            }
+            let next = tokens
+                .next()
+                .and_then(|token| self.next_state(state, &token));
+            match next {
+                Some(next) => state = next,
+                None => return false,
             }
-            // }
-            return None;
         }
-
-        // let eps_transition = possible_transitions.get(&None)?;
-        // for transition in eps_transition {
-        //     debug_println!("  {} --{:?}--> {:2} {}", state, EPS, transition, text);
-        //     let result = self.recursive_parse(text, *transition);
-        //     if result.is_some() {
-        //         return result;
-        //     }
-        // }
-        return None;
     }
 }
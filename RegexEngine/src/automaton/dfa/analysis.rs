@@ -0,0 +1,187 @@
+use super::DFA;
+use crate::type_defs::State;
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Debug,
+    hash::Hash,
+};
+
+/// Number of `char` values: every code point except the surrogates.
+pub const CHAR_COUNT: u128 = 0x110000 - 0x800;
+
+impl<T: Eq + Hash + Debug + Clone> DFA<T> {
+    /// No string is accepted.
+    pub fn is_empty(&self) -> bool {
+        self.useful_states().is_empty()
+    }
+
+    /// Every string over every symbol is accepted.
+    pub fn is_universal(&self) -> bool {
+        self.complement().is_empty()
+    }
+
+    /// Finitely many strings are accepted, meaning that no cycle lies on a
+    /// path from the start to a final state. A default transition on such a
+    /// path only adds finitely many strings, as long as the symbols are finite.
+    pub fn is_finite(&self) -> bool {
+        self.topological_order().is_some()
+    }
+
+    /// Length of the shortest accepted string.
+    pub fn min_length(&self) -> Option<usize> {
+        let useful = self.useful_states();
+        let start = self.start?;
+        if !useful.contains(&start) {
+            return None;
+        }
+        let mut distances: HashMap<State, usize> = HashMap::from([(start, 0)]);
+        let mut queue: VecDeque<State> = VecDeque::from([start]);
+        while let Some(state) = queue.pop_front() {
+            if self.is_final(state) {
+                return Some(distances[&state]);
+            }
+            for next in self.successors(state) {
+                if useful.contains(&next) && !distances.contains_key(&next) {
+                    distances.insert(next, distances[&state] + 1);
+                    queue.push_back(next);
+                }
+            }
+        }
+        return None;
+    }
+
+    /// Length of the longest accepted string, when the language is finite and
+    /// not empty.
+    pub fn max_length(&self) -> Option<usize> {
+        let order = self.topological_order()?;
+        // Every useful state is reached from the start, the first one in order
+        let mut longest: HashMap<State, usize> = order.iter().map(|state| (*state, 0)).collect();
+        for state in order.iter() {
+            let length = longest[state];
+            for next in self.successors(*state) {
+                if let Some(known) = longest.get_mut(&next) {
+                    *known = (*known).max(length + 1);
+                }
+            }
+        }
+        order
+            .iter()
+            .filter(|state| self.is_final(**state))
+            .map(|state| longest[state])
+            .max()
+    }
+
+    fn is_final(&self, state: State) -> bool {
+        self.end
+            .as_ref()
+            .is_some_and(|end| end.contains_key(&state))
+    }
+
+    // Targets of the explicit and default transitions of `state`
+    fn successors(&self, state: State) -> impl Iterator<Item = State> + '_ {
+        let explicit = self
+            .automaton
+            .get(&state)
+            .into_iter()
+            .flat_map(|t| t.values());
+        explicit.chain(self.others.get(&state)).cloned()
+    }
+
+    // States on some path from the start to a final state
+    pub(super) fn useful_states(&self) -> HashSet<State> {
+        let Some(start) = self.start else {
+            return HashSet::new();
+        };
+        let mut reachable: HashSet<State> = HashSet::from([start]);
+        let mut work_list: Vec<State> = vec![start];
+        let mut predecessors: HashMap<State, Vec<State>> = HashMap::new();
+        while let Some(state) = work_list.pop() {
+            for next in self.successors(state) {
+                predecessors.entry(next).or_default().push(state);
+                if reachable.insert(next) {
+                    work_list.push(next);
+                }
+            }
+        }
+
+        let mut useful: HashSet<State> = HashSet::new();
+        let mut work_list: Vec<State> = reachable
+            .iter()
+            .filter(|state| self.is_final(**state))
+            .cloned()
+            .collect();
+        useful.extend(work_list.iter());
+        while let Some(state) = work_list.pop() {
+            for previous in predecessors.get(&state).into_iter().flatten() {
+                if useful.insert(*previous) {
+                    work_list.push(*previous);
+                }
+            }
+        }
+        useful
+    }
+
+    // Useful states with every transition going forward, starting with the
+    // start state. None when they contain a cycle
+    fn topological_order(&self) -> Option<Vec<State>> {
+        let useful = self.useful_states();
+        let mut incoming: HashMap<State, usize> = useful.iter().map(|state| (*state, 0)).collect();
+        for state in useful.iter() {
+            for next in self.successors(*state).filter(|next| useful.contains(next)) {
+                *incoming.get_mut(&next).unwrap() += 1;
+            }
+        }
+
+        let mut order: Vec<State> = Vec::with_capacity(useful.len());
+        let mut ready: Vec<State> = incoming
+            .iter()
+            .filter(|(_, count)| **count == 0)
+            .map(|(state, _)| *state)
+            .collect();
+        while let Some(state) = ready.pop() {
+            order.push(state);
+            for next in self.successors(state).filter(|next| useful.contains(next)) {
+                let count = incoming.get_mut(&next).unwrap();
+                *count -= 1;
+                if *count == 0 {
+                    ready.push(next);
+                }
+            }
+        }
+        if order.len() < useful.len() {
+            return None;
+        }
+        return Some(order);
+    }
+}
+
+impl DFA<char> {
+    /// Number of accepted strings of exactly `length` characters, saturating
+    /// at `u128::MAX`. A default transition stands for every character its
+    /// state has no entry for.
+    pub fn count_strings_of_length(&self, length: usize) -> u128 {
+        let Some(start) = self.start else {
+            return 0;
+        };
+        let mut ways: HashMap<State, u128> = HashMap::from([(start, 1)]);
+        for _ in 0..length {
+            let mut next_ways: HashMap<State, u128> = HashMap::new();
+            for (state, count) in ways {
+                let transitions = &self.automaton[&state];
+                for next in transitions.values() {
+                    let entry = next_ways.entry(*next).or_default();
+                    *entry = entry.saturating_add(count);
+                }
+                if let Some(next) = self.others.get(&state) {
+                    let characters = CHAR_COUNT - transitions.len() as u128;
+                    let entry = next_ways.entry(*next).or_default();
+                    *entry = entry.saturating_add(count.saturating_mul(characters));
+                }
+            }
+            ways = next_ways;
+        }
+        ways.into_iter()
+            .filter(|(state, _)| self.is_final(*state))
+            .fold(0, |total: u128, (_, count)| total.saturating_add(count))
+    }
+}
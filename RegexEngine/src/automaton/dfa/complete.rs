@@ -0,0 +1,93 @@
+use super::DFA;
+use crate::type_defs::{PatternId, State};
+use std::{collections::HashMap, fmt::Debug, hash::Hash};
+
+/// A DFA with a total transition function, laid out over dense indices.
+///
+/// Missing transitions of the original automaton lead to an explicit sink,
+/// which is the last index and loops on every symbol. Every row has one more
+/// column than the alphabet, taken on the symbols outside of it. A state
+/// accepts the patterns listed in `labels`, so non-final states have an empty label.
+pub(crate) struct CompleteDFA<T> {
+    pub(crate) alphabet: Vec<T>,
+    pub(crate) delta: Vec<Vec<usize>>,
+    pub(crate) labels: Vec<Vec<PatternId>>,
+    pub(crate) start: usize,
+    pub(crate) sink: usize,
+}
+
+impl<T: Eq + Hash + Debug + Clone> DFA<T> {
+    pub(crate) fn complete(&self, alphabet: Vec<T>) -> CompleteDFA<T> {
+        let mut states: Vec<State> = self.automaton.keys().cloned().collect();
+        states.sort();
+        let index: HashMap<State, usize> = states
+            .iter()
+            .enumerate()
+            .map(|(position, state)| (*state, position))
+            .collect();
+        let sink = states.len();
+
+        let mut delta: Vec<Vec<usize>> = Vec::with_capacity(sink + 1);
+        for state in states.iter() {
+            let other = self.others.get(state).map_or(sink, |next| index[next]);
+            let mut row: Vec<usize> = alphabet
+                .iter()
+                .map(|token| {
+                    self.next_state(*state, token)
+                        .map_or(sink, |next| index[&next])
+                })
+                .collect();
+            row.push(other);
+            delta.push(row);
+        }
+        delta.push(vec![sink; alphabet.len() + 1]);
+
+        let end = self.end.clone().unwrap_or_default();
+        let mut labels: Vec<Vec<PatternId>> = states
+            .iter()
+            .map(|state| end.get(state).cloned().unwrap_or_default())
+            .collect();
+        labels.push(Vec::new());
+
+        CompleteDFA {
+            start: index[&self.start.unwrap()],
+            alphabet,
+            delta,
+            labels,
+            sink,
+        }
+    }
+}
+
+impl<T: Eq + Hash + Debug + Clone> CompleteDFA<T> {
+    /// The same transitions as a `DFA`, where index `i` becomes state `i + 1`
+    /// and accepts the patterns in `labels[i]`.
+    pub(crate) fn to_dfa(&self, labels: Vec<Vec<PatternId>>) -> DFA<T> {
+        let other = self.alphabet.len();
+        let mut automaton: HashMap<State, HashMap<T, State>> = HashMap::new();
+        let mut others: HashMap<State, State> = HashMap::new();
+        for (index, row) in self.delta.iter().enumerate() {
+            let state = index as State + 1;
+            let transitions = self
+                .alphabet
+                .iter()
+                .zip(row.iter())
+                .map(|(token, next)| (token.clone(), *next as State + 1))
+                .collect();
+            automaton.insert(state, transitions);
+            others.insert(state, row[other] as State + 1);
+        }
+        let end = labels
+            .into_iter()
+            .enumerate()
+            .filter(|(_, label)| !label.is_empty())
+            .map(|(index, label)| (index as State + 1, label))
+            .collect();
+        DFA {
+            automaton,
+            start: Some(self.start as State + 1),
+            end: Some(end),
+            others,
+        }
+    }
+}
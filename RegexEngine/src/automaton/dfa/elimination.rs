@@ -0,0 +1,181 @@
+use super::DFA;
+use crate::tree::Node;
+use crate::type_defs::State;
+use std::collections::{HashMap, HashSet};
+
+// Labels of the edges between the states left, keyed by (from, to). A missing
+// edge stands for the empty language
+type Edges = HashMap<(State, State), Node<char>>;
+
+impl DFA<char> {
+    /// Syntax tree of a pattern accepting the same strings, found by state
+    /// elimination. None when the language is empty.
+    ///
+    /// States with the fewest paths running through them are eliminated first,
+    /// which keeps the labels from growing more than they have to. Default
+    /// transitions are written with `~`, so only `derivative::Term` reads
+    /// those patterns back.
+    pub fn to_ast(&self) -> Option<Node<char>> {
+        let useful = self.useful_states();
+        if useful.is_empty() {
+            return None;
+        }
+
+        // A fresh start and end keep the original states free to be eliminated
+        let start = useful.iter().max().unwrap() + 1;
+        let end = start + 1;
+        let mut edges: Edges = HashMap::new();
+        add_edge(&mut edges, start, self.start?, empty());
+        for state in useful.iter() {
+            if self.end.as_ref().is_some_and(|end| end.contains_key(state)) {
+                add_edge(&mut edges, *state, end, empty());
+            }
+            let mut transitions: Vec<(char, State)> = self.automaton[state]
+                .iter()
+                .filter(|(_, next)| useful.contains(next))
+                .map(|(token, next)| (*token, *next))
+                .collect();
+            transitions.sort();
+            for (token, next) in transitions {
+                add_edge(&mut edges, *state, next, literal(token));
+            }
+            if let Some(next) = self.others.get(state).filter(|next| useful.contains(next)) {
+                let mut tokens: Vec<char> = self.automaton[state].keys().cloned().collect();
+                tokens.sort();
+                add_edge(&mut edges, *state, *next, other_than(tokens));
+            }
+        }
+
+        let mut remaining: HashSet<State> = useful;
+        while let Some(state) = next_to_eliminate(&edges, &remaining) {
+            remaining.remove(&state);
+            eliminate(&mut edges, state);
+        }
+        edges.remove(&(start, end))
+    }
+
+    /// Pattern accepting the same strings, as text. See `to_ast`.
+    pub fn to_regex(&self) -> Option<String> {
+        self.to_ast().map(|tree| tree.to_regex())
+    }
+}
+
+// The state whose predecessors times successors is the smallest
+fn next_to_eliminate(edges: &Edges, remaining: &HashSet<State>) -> Option<State> {
+    let mut degrees: HashMap<State, (usize, usize)> = HashMap::new();
+    for (from, to) in edges.keys().filter(|(from, to)| from != to) {
+        degrees.entry(*from).or_default().1 += 1;
+        degrees.entry(*to).or_default().0 += 1;
+    }
+    remaining
+        .iter()
+        .min_by_key(|state| {
+            let (incoming, outgoing) = degrees.get(state).cloned().unwrap_or_default();
+            (incoming * outgoing, **state)
+        })
+        .cloned()
+}
+
+// Replaces every path p -> state -> r by a direct edge p -> r labelled with
+// the label of p -> state, the loop on state repeated, then state -> r
+fn eliminate(edges: &mut Edges, state: State) {
+    let repeat = edges.remove(&(state, state)).map(star);
+    let incoming: Vec<(State, Node<char>)> = take_edges(edges, |(_, to)| to == state)
+        .map(|((from, _), label)| (from, label))
+        .collect();
+    let outgoing: Vec<(State, Node<char>)> = take_edges(edges, |(from, _)| from == state)
+        .map(|((_, to), label)| (to, label))
+        .collect();
+    for (from, before) in incoming.iter() {
+        for (to, after) in outgoing.iter() {
+            let mut label = before.clone();
+            if let Some(repeat) = &repeat {
+                label = concat(label, repeat.clone());
+            }
+            add_edge(edges, *from, *to, concat(label, after.clone()));
+        }
+    }
+}
+
+fn take_edges(
+    edges: &mut Edges,
+    matches: impl Fn((State, State)) -> bool,
+) -> impl Iterator<Item = ((State, State), Node<char>)> {
+    let mut keys: Vec<(State, State)> = edges.keys().cloned().filter(|key| matches(*key)).collect();
+    keys.sort();
+    let taken: Vec<((State, State), Node<char>)> = keys
+        .into_iter()
+        .map(|key| (key, edges.remove(&key).unwrap()))
+        .collect();
+    taken.into_iter()
+}
+
+fn add_edge(edges: &mut Edges, from: State, to: State, label: Node<char>) {
+    let label = match edges.remove(&(from, to)) {
+        Some(existing) => union(existing, label),
+        None => label,
+    };
+    edges.insert((from, to), label);
+}
+
+fn empty() -> Node<char> {
+    Node::epsilon()
+}
+
+fn literal(token: char) -> Node<char> {
+    Node {
+        value: token,
+        left: None,
+        right: None,
+    }
+}
+
+// Any single character but `tokens`: the complement of the empty string, the
+// tokens and every string of two characters or more, `~((?:)|a|b|~(?:)~(?:))`
+fn other_than(tokens: Vec<char>) -> Node<char> {
+    let non_empty = Node::with_l('~', empty());
+    let longer = Node::with_lr('·', non_empty.clone(), non_empty);
+    let mut excluded = empty();
+    for token in tokens {
+        excluded = Node::with_lr('|', excluded, literal(token));
+    }
+    Node::with_l('~', Node::with_lr('|', excluded, longer))
+}
+
+fn is_empty_string(node: &Node<char>) -> bool {
+    node.is_epsilon()
+}
+
+// The constructors below drop the parts that change nothing, such as an empty
+// string in a concatenation or a repeated branch in an alternation
+
+fn union(left: Node<char>, right: Node<char>) -> Node<char> {
+    if left == right {
+        return left;
+    }
+    // A repetition already matches the empty string
+    if is_empty_string(&left) && right.value == '*' && !right.is_leaf() {
+        return right;
+    }
+    if is_empty_string(&right) && left.value == '*' && !left.is_leaf() {
+        return left;
+    }
+    Node::with_lr('|', left, right)
+}
+
+fn concat(left: Node<char>, right: Node<char>) -> Node<char> {
+    if is_empty_string(&left) {
+        return right;
+    }
+    if is_empty_string(&right) {
+        return left;
+    }
+    Node::with_lr('·', left, right)
+}
+
+fn star(body: Node<char>) -> Node<char> {
+    if is_empty_string(&body) || (body.value == '*' && !body.is_leaf()) {
+        return body;
+    }
+    Node::with_l('*', body)
+}
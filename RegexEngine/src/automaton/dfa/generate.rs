@@ -0,0 +1,243 @@
+use super::DFA;
+use crate::type_defs::State;
+use std::collections::HashMap;
+
+/// SplitMix64 generator. Not meant for anything but reproducible test data.
+#[derive(Debug, Clone)]
+pub struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    pub fn new(seed: u64) -> Self {
+        SplitMix64 { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Number in `0..bound`. The modulo bias is at most `bound / 2^128`.
+    pub fn below(&mut self, bound: u128) -> u128 {
+        let high = self.next_u64() as u128;
+        let low = self.next_u64() as u128;
+        ((high << 64) | low) % bound
+    }
+}
+
+/// Accepted strings in shortlex order: shorter ones first, then
+/// lexicographically by character.
+pub struct Strings {
+    dfa: DFA<char>,
+    alphabet: Vec<char>,
+    // suffixes[r][state] counts the strings of length r leading to a final state
+    suffixes: Vec<HashMap<State, u128>>,
+    // No string is this long or longer, when the language is finite
+    limit: Option<usize>,
+    length: usize,
+    // State and next symbol to try at every depth of the current word
+    stack: Vec<(State, usize)>,
+    word: Vec<char>,
+}
+
+impl Iterator for Strings {
+    type Item = String;
+
+    fn next(&mut self) -> Option<String> {
+        loop {
+            if self.stack.is_empty() {
+                if self.limit.is_some_and(|limit| self.length >= limit) {
+                    return None;
+                }
+                let start = self.dfa.start?;
+                if self
+                    .dfa
+                    .suffix_count(&mut self.suffixes, self.length, start)
+                    > 0
+                {
+                    self.stack.push((start, 0));
+                } else {
+                    self.length += 1;
+                    continue;
+                }
+            }
+
+            let remaining = self.length - self.word.len();
+            if remaining == 0 {
+                let found: String = self.word.iter().collect();
+                self.backtrack();
+                return Some(found);
+            }
+            let (state, symbol) = *self.stack.last().unwrap();
+            if symbol == self.alphabet.len() {
+                self.backtrack();
+                continue;
+            }
+            self.stack.last_mut().unwrap().1 += 1;
+            let token = self.alphabet[symbol];
+            let Some(next) = self.dfa.next_state(state, &token) else {
+                continue;
+            };
+            // Only branches that can still end in a final state are entered
+            if self
+                .dfa
+                .suffix_count(&mut self.suffixes, remaining - 1, next)
+                > 0
+            {
+                self.stack.push((next, 0));
+                self.word.push(token);
+            }
+        }
+    }
+}
+
+impl Strings {
+    // Leaves the deepest state. Once the root is left, the next length starts
+    fn backtrack(&mut self) {
+        self.stack.pop();
+        if self.stack.is_empty() {
+            self.length += 1;
+        }
+        self.word.pop();
+    }
+}
+
+impl DFA<char> {
+    /// Every accepted string over the characters the automaton mentions, in
+    /// shortlex order. Default transitions are taken for those characters,
+    /// but never stand for the ones it does not mention.
+    pub fn strings(&self) -> Strings {
+        let dfa = self.restricted();
+        let mut alphabet: Vec<char> = dfa.get_alphabet().into_iter().collect();
+        alphabet.sort();
+        let limit = match dfa.is_finite() {
+            true => Some(dfa.max_length().map_or(0, |max| max + 1)),
+            false => None,
+        };
+        Strings {
+            dfa,
+            alphabet,
+            suffixes: Vec::new(),
+            limit,
+            length: 0,
+            stack: Vec::new(),
+            word: Vec::new(),
+        }
+    }
+
+    /// Accepted string of at most `max_length` characters, drawn uniformly
+    /// among all of them over the characters the automaton mentions. Counts
+    /// past `u128::MAX` saturate, which skews the draw for huge languages.
+    pub fn sample(&self, max_length: usize, rng: &mut SplitMix64) -> Option<String> {
+        let dfa = self.restricted();
+        let mut alphabet: Vec<char> = dfa.get_alphabet().into_iter().collect();
+        alphabet.sort();
+        let start = dfa.start?;
+
+        let mut suffixes: Vec<HashMap<State, u128>> = Vec::new();
+        let counts: Vec<u128> = (0..=max_length)
+            .map(|length| dfa.suffix_count(&mut suffixes, length, start))
+            .collect();
+        let total = counts
+            .iter()
+            .fold(0u128, |sum, count| sum.saturating_add(*count));
+        if total == 0 {
+            return None;
+        }
+        let mut length = pick(&counts, rng.below(total));
+
+        // Every character is weighted by the strings it leaves room for
+        let mut word = String::new();
+        let mut state = start;
+        while length > 0 {
+            length -= 1;
+            let choices: Vec<(char, State)> = alphabet
+                .iter()
+                .filter_map(|token| Some((*token, dfa.next_state(state, token)?)))
+                .collect();
+            let weights: Vec<u128> = choices
+                .iter()
+                .map(|(_, next)| dfa.suffix_count(&mut suffixes, length, *next))
+                .collect();
+            let total = weights
+                .iter()
+                .fold(0u128, |sum, weight| sum.saturating_add(*weight));
+            let choice = pick(&weights, rng.below(total));
+            word.push(choices[choice].0);
+            state = choices[choice].1;
+        }
+        return Some(word);
+    }
+
+    // Same language over the mentioned characters only, with the default
+    // transitions spelled out for each of them
+    fn restricted(&self) -> DFA<char> {
+        let alphabet = self.get_alphabet();
+        let automaton = self
+            .automaton
+            .keys()
+            .map(|state| {
+                let transitions = alphabet
+                    .iter()
+                    .filter_map(|token| Some((*token, self.next_state(*state, token)?)))
+                    .collect();
+                (*state, transitions)
+            })
+            .collect();
+        DFA {
+            automaton,
+            start: self.start,
+            end: self.end.clone(),
+            others: HashMap::new(),
+        }
+    }
+
+    // Number of strings of `length` characters leading from `state` to a
+    // final state, filling in the rows of `suffixes` up to `length`
+    fn suffix_count(
+        &self,
+        suffixes: &mut Vec<HashMap<State, u128>>,
+        length: usize,
+        state: State,
+    ) -> u128 {
+        while suffixes.len() <= length {
+            let row: HashMap<State, u128> = match suffixes.last() {
+                None => self
+                    .end
+                    .iter()
+                    .flat_map(|end| end.keys())
+                    .map(|state| (*state, 1))
+                    .collect(),
+                Some(previous) => self
+                    .automaton
+                    .iter()
+                    .map(|(state, transitions)| {
+                        let count = transitions
+                            .values()
+                            .filter_map(|next| previous.get(next))
+                            .fold(0u128, |sum, count| sum.saturating_add(*count));
+                        (*state, count)
+                    })
+                    .filter(|(_, count)| *count > 0)
+                    .collect(),
+            };
+            suffixes.push(row);
+        }
+        suffixes[length].get(&state).cloned().unwrap_or(0)
+    }
+}
+
+// Index whose cumulative weight first exceeds `target`
+fn pick(weights: &[u128], mut target: u128) -> usize {
+    for (index, weight) in weights.iter().enumerate() {
+        if target < *weight {
+            return index;
+        }
+        target -= weight;
+    }
+    weights.len() - 1
+}
@@ -0,0 +1,294 @@
+use super::{complete::CompleteDFA, DFA};
+use crate::automaton::alphabet::CharClasses;
+use crate::type_defs::{PatternId, State};
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Debug,
+    hash::Hash,
+};
+
+/// Partition of `0..n` whose blocks are contiguous ranges of `elements`.
+/// Splitting a block only touches the elements that were marked.
+struct Partition {
+    elements: Vec<usize>,
+    location: Vec<usize>,
+    block_of: Vec<usize>,
+    first: Vec<usize>,
+    middle: Vec<usize>,
+    end: Vec<usize>,
+}
+
+impl Partition {
+    // One initial block per distinct label, so that states accepting
+    // different patterns are never merged
+    fn new(labels: &[Vec<PatternId>]) -> Self {
+        let mut elements: Vec<usize> = (0..labels.len()).collect();
+        elements.sort_by(|a, b| labels[*a].cmp(&labels[*b]));
+
+        let mut partition = Partition {
+            location: vec![0; elements.len()],
+            block_of: vec![0; elements.len()],
+            first: Vec::new(),
+            middle: Vec::new(),
+            end: Vec::new(),
+            elements,
+        };
+        for (position, element) in partition.elements.iter().enumerate() {
+            partition.location[*element] = position;
+        }
+        let mut first = 0;
+        for end in 1..=labels.len() {
+            let element = partition.elements[first];
+            if end == labels.len() || labels[partition.elements[end]] != labels[element] {
+                partition.push_block(first, end);
+                first = end;
+            }
+        }
+        partition
+    }
+
+    fn push_block(&mut self, first: usize, end: usize) -> usize {
+        let block = self.first.len();
+        self.first.push(first);
+        self.middle.push(first);
+        self.end.push(end);
+        for position in first..end {
+            self.block_of[self.elements[position]] = block;
+        }
+        block
+    }
+
+    fn len(&self) -> usize {
+        self.first.len()
+    }
+
+    fn block_size(&self, block: usize) -> usize {
+        self.end[block] - self.first[block]
+    }
+
+    fn members(&self, block: usize) -> &[usize] {
+        &self.elements[self.first[block]..self.end[block]]
+    }
+
+    /// Moves `element` into the marked prefix of its block. Returns true when
+    /// it is the first mark of that block.
+    fn mark(&mut self, element: usize) -> bool {
+        let block = self.block_of[element];
+        let position = self.location[element];
+        let middle = self.middle[block];
+        if position < middle {
+            return false;
+        }
+        let other = self.elements[middle];
+        self.elements.swap(position, middle);
+        self.location[other] = position;
+        self.location[element] = middle;
+        self.middle[block] += 1;
+        middle == self.first[block]
+    }
+
+    /// Splits the marked prefix off `block`. Returns the new block, if any.
+    fn split(&mut self, block: usize) -> Option<usize> {
+        let middle = self.middle[block];
+        self.middle[block] = self.first[block];
+        if middle == self.end[block] {
+            return None;
+        }
+        let first = self.first[block];
+        self.first[block] = middle;
+        self.middle[block] = middle;
+        Some(self.push_block(first, middle))
+    }
+}
+
+impl<T: Eq + Hash + Debug + Clone> DFA<T> {
+    /// Hopcroft's partition refinement over the completed automaton.
+    ///
+    /// States equivalent to the sink can never reach a final state, so they are
+    /// dropped together with it and the result stays a partial DFA. A state
+    /// with a live default transition keeps its dying characters pointed at
+    /// one dead state, or they would follow the default instead.
+    pub fn minimize_from(dfa: DFA<T>) -> Option<Self> {
+        let alphabet: Vec<Vec<T>> = dfa
+            .get_alphabet()
+            .into_iter()
+            .map(|token| vec![token])
+            .collect();
+        Some(Self::hopcroft(dfa, alphabet))
+    }
+
+    // `groups` partitions the alphabet into symbols known to share every
+    // transition, so only one representative of each is refined
+    fn hopcroft(dfa: DFA<T>, groups: Vec<Vec<T>>) -> Self {
+        let mut dfa = dfa;
+        dfa.remove_unreachable();
+
+        let alphabet: Vec<T> = groups.iter().map(|group| group[0].clone()).collect();
+        let complete = dfa.complete(alphabet);
+        // The last column holds the default transitions
+        let symbols = complete.alphabet.len() + 1;
+        let size = complete.delta.len();
+
+        // inverse[symbol][state] lists the states reaching `state` with `symbol`
+        let mut inverse: Vec<Vec<Vec<usize>>> = vec![vec![Vec::new(); size]; symbols];
+        for (state, row) in complete.delta.iter().enumerate() {
+            for (symbol, next) in row.iter().enumerate() {
+                inverse[symbol][*next].push(state);
+            }
+        }
+
+        let mut partition = Partition::new(&complete.labels);
+        let mut work_list: VecDeque<(usize, usize)> = VecDeque::new();
+        // pending[block * symbols + symbol] is set while the pair sits in the work list
+        let mut pending: Vec<bool> = vec![false; partition.len() * symbols];
+
+        // Every initial block but the largest one has to be used as a splitter
+        let largest = (0..partition.len())
+            .max_by_key(|block| partition.block_size(*block))
+            .unwrap();
+        for block in (0..partition.len()).filter(|block| *block != largest) {
+            for symbol in 0..symbols {
+                work_list.push_back((block, symbol));
+                pending[block * symbols + symbol] = true;
+            }
+        }
+
+        let mut touched: Vec<usize> = Vec::new();
+        while let Some((splitter, symbol)) = work_list.pop_front() {
+            pending[splitter * symbols + symbol] = false;
+
+            let predecessors: Vec<usize> = partition
+                .members(splitter)
+                .iter()
+                .flat_map(|state| inverse[symbol][*state].iter().cloned())
+                .collect();
+            for state in predecessors {
+                if partition.mark(state) {
+                    touched.push(partition.block_of[state]);
+                }
+            }
+
+            for block in touched.drain(..) {
+                let Some(new_block) = partition.split(block) else {
+                    continue;
+                };
+                pending.resize(partition.len() * symbols, false);
+                for symbol in 0..symbols {
+                    // Refining with the smaller half is enough unless the old
+                    // block is still waiting to be used as a splitter
+                    let chosen = if pending[block * symbols + symbol]
+                        || partition.block_size(new_block) <= partition.block_size(block)
+                    {
+                        new_block
+                    } else {
+                        block
+                    };
+                    if !pending[chosen * symbols + symbol] {
+                        pending[chosen * symbols + symbol] = true;
+                        work_list.push_back((chosen, symbol));
+                    }
+                }
+            }
+        }
+
+        Self::quotient(&complete, &partition, &groups)
+    }
+
+    /// Brzozowski's algorithm: determinizing the reversal of a reachable DFA
+    /// gives a minimal DFA of the reversed language, so doing it twice
+    /// yields the minimal DFA of the original one.
+    ///
+    /// Default transitions cannot be reversed, so a DFA with any is minimized
+    /// by `minimize_from` instead.
+    pub fn minimize_brzozowski(dfa: DFA<T>) -> Option<Self> {
+        if !dfa.others.is_empty() {
+            return Self::minimize_from(dfa);
+        }
+        let reversed = dfa.determinize_reverse()?;
+        reversed.determinize_reverse()
+    }
+
+    // The fresh start of `reverse` would end up in the first subset and keep it
+    // apart from an otherwise equal one, so the subsets start from the old finals
+    fn determinize_reverse(&self) -> Option<Self> {
+        let finals: Vec<State> = self
+            .end
+            .iter()
+            .flat_map(|end| end.keys())
+            .cloned()
+            .collect();
+        DFA::from_efa_starting_at(&self.reverse(), &finals)
+    }
+
+    // Builds the automaton whose states are the blocks, numbered in visiting order
+    fn quotient(complete: &CompleteDFA<T>, partition: &Partition, groups: &[Vec<T>]) -> Self {
+        let dead = partition.block_of[complete.sink];
+        let start_block = partition.block_of[complete.start];
+
+        let mut numbering: HashMap<usize, State> = HashMap::new();
+        let mut automaton: HashMap<State, HashMap<T, State>> = HashMap::new();
+        let mut end: HashMap<State, Vec<PatternId>> = HashMap::new();
+        let mut others: HashMap<State, State> = HashMap::new();
+        let mut queue: VecDeque<usize> = VecDeque::new();
+
+        numbering.insert(start_block, 1);
+        queue.push_back(start_block);
+        while let Some(block) = queue.pop_front() {
+            let state = numbering[&block];
+            let representative = partition.members(block)[0];
+            if !complete.labels[representative].is_empty() {
+                end.insert(state, complete.labels[representative].clone());
+            }
+
+            let mut transitions: HashMap<T, State> = HashMap::new();
+            if block != dead {
+                let row = &complete.delta[representative];
+                // Characters that die have to be kept apart from a live default
+                let default_lives = row
+                    .get(groups.len())
+                    .is_some_and(|next| partition.block_of[*next] != dead);
+                for (symbol, next) in row.iter().enumerate() {
+                    let next_block = partition.block_of[*next];
+                    if next_block == dead && !default_lives {
+                        continue;
+                    }
+                    let next_count = numbering.len() as State + 1;
+                    let next_state = *numbering.entry(next_block).or_insert_with(|| {
+                        queue.push_back(next_block);
+                        next_count
+                    });
+                    match groups.get(symbol) {
+                        Some(group) => {
+                            for token in group.iter() {
+                                transitions.insert(token.clone(), next_state);
+                            }
+                        }
+                        None => {
+                            others.insert(state, next_state);
+                        }
+                    }
+                }
+            }
+            automaton.insert(state, transitions);
+        }
+
+        DFA {
+            automaton,
+            start: Some(1),
+            end: Some(end),
+            others,
+        }
+    }
+}
+
+impl DFA<char> {
+    /// Hopcroft minimization that refines one column per character class
+    /// instead of one per character.
+    pub fn minimize_with_classes(dfa: DFA<char>, classes: &CharClasses) -> Option<Self> {
+        let groups: Vec<Vec<char>> = (1..classes.len())
+            .map(|class| classes.members(class).to_vec())
+            .filter(|members| !members.is_empty())
+            .collect();
+        Some(Self::hopcroft(dfa, groups))
+    }
+}
@@ -0,0 +1,135 @@
+use super::{complete::CompleteDFA, DFA};
+use crate::type_defs::{PatternId, State};
+use std::{
+    collections::{hash_map::Entry, HashMap, HashSet, VecDeque},
+    fmt::Debug,
+    hash::Hash,
+};
+
+impl<T: Eq + Hash + Debug + Clone> DFA<T> {
+    /// Minimal DFA of the strings both `left` and `right` accept.
+    pub fn intersect(left: &DFA<T>, right: &DFA<T>) -> Self {
+        Self::product(left, right, |left, right| left && right)
+    }
+
+    /// Minimal DFA of the strings `left` or `right` accepts.
+    pub fn union(left: &DFA<T>, right: &DFA<T>) -> Self {
+        Self::product(left, right, |left, right| left || right)
+    }
+
+    /// Minimal DFA of the strings `left` accepts and `right` does not.
+    pub fn difference(left: &DFA<T>, right: &DFA<T>) -> Self {
+        Self::product(left, right, |left, right| left && !right)
+    }
+
+    /// Some string is accepted by both automata.
+    pub fn overlaps(left: &DFA<T>, right: &DFA<T>) -> bool {
+        let intersection = Self::intersect(left, right);
+        intersection.end.is_some_and(|end| !end.is_empty())
+    }
+
+    // Runs both completed automata side by side over their common alphabet.
+    // A pair is final when `accepts` holds for the finality of its members
+    fn product(left: &DFA<T>, right: &DFA<T>, accepts: impl Fn(bool, bool) -> bool) -> Self {
+        let mut alphabet: HashSet<T> = left.get_alphabet();
+        alphabet.extend(right.get_alphabet());
+        let alphabet: Vec<T> = alphabet.into_iter().collect();
+        let left: CompleteDFA<T> = left.complete(alphabet.clone());
+        let right: CompleteDFA<T> = right.complete(alphabet.clone());
+
+        let mut numbering: HashMap<(usize, usize), State> = HashMap::new();
+        let mut automaton: HashMap<State, HashMap<T, State>> = HashMap::new();
+        let mut end: HashMap<State, Vec<PatternId>> = HashMap::new();
+        let mut others: HashMap<State, State> = HashMap::new();
+        let mut queue: VecDeque<(usize, usize)> = VecDeque::new();
+
+        let start = (left.start, right.start);
+        numbering.insert(start, 1);
+        queue.push_back(start);
+        while let Some((l, r)) = queue.pop_front() {
+            let state = numbering[&(l, r)];
+            if accepts(!left.labels[l].is_empty(), !right.labels[r].is_empty()) {
+                end.insert(state, vec![0]);
+            }
+
+            let mut transitions: HashMap<T, State> = HashMap::new();
+            for (symbol, token) in alphabet.iter().enumerate() {
+                let next = (left.delta[l][symbol], right.delta[r][symbol]);
+                let next_count = numbering.len() as State + 1;
+                let next_state = *numbering.entry(next).or_insert_with(|| {
+                    queue.push_back(next);
+                    next_count
+                });
+                transitions.insert(token.clone(), next_state);
+            }
+            automaton.insert(state, transitions);
+
+            // Symbols neither automaton mentions follow both default transitions
+            let other = alphabet.len();
+            let next = (left.delta[l][other], right.delta[r][other]);
+            let next_count = numbering.len() as State + 1;
+            let next_state = *numbering.entry(next).or_insert_with(|| {
+                queue.push_back(next);
+                next_count
+            });
+            others.insert(state, next_state);
+        }
+
+        let product = DFA {
+            automaton,
+            start: Some(1),
+            end: Some(end),
+            others,
+        };
+        // Pairs that cannot reach a final state merge with the sink and vanish
+        Self::minimize_from(product).unwrap()
+    }
+}
+
+impl DFA<char> {
+    /// Shortest string for which `accepts` holds of its membership in `left`
+    /// and in `right`, found breadth first over the product. Among strings of
+    /// the same length the smallest characters are tried first.
+    pub fn shortest_witness(
+        left: &DFA<char>,
+        right: &DFA<char>,
+        accepts: impl Fn(bool, bool) -> bool,
+    ) -> Option<String> {
+        let mut alphabet: Vec<char> = left.get_alphabet().into_iter().collect();
+        alphabet.extend(right.get_alphabet());
+        alphabet.sort();
+        alphabet.dedup();
+        let left: CompleteDFA<char> = left.complete(alphabet.clone());
+        let right: CompleteDFA<char> = right.complete(alphabet.clone());
+        // Stands for every character outside of the alphabet
+        let other = ('a'..=char::MAX).find(|token| !alphabet.contains(token))?;
+
+        // Reached pairs, with the pair and the character they were reached from
+        type Pair = (usize, usize);
+        let mut parents: HashMap<Pair, Option<(Pair, char)>> = HashMap::new();
+        let mut queue: VecDeque<Pair> = VecDeque::new();
+        let start = (left.start, right.start);
+        parents.insert(start, None);
+        queue.push_back(start);
+        while let Some((l, r)) = queue.pop_front() {
+            if accepts(!left.labels[l].is_empty(), !right.labels[r].is_empty()) {
+                let mut word: Vec<char> = Vec::new();
+                let mut pair = (l, r);
+                while let Some((parent, token)) = parents[&pair] {
+                    word.push(token);
+                    pair = parent;
+                }
+                return Some(word.into_iter().rev().collect());
+            }
+            let tokens = alphabet.iter().cloned().chain([other]);
+            for (symbol, token) in tokens.enumerate() {
+                let next = (left.delta[l][symbol], right.delta[r][symbol]);
+                if let Entry::Vacant(entry) = parents.entry(next) {
+                    entry.insert(Some(((l, r), token)));
+                    queue.push_back(next);
+                }
+            }
+        }
+        return None;
+    }
+}
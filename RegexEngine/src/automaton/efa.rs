@@ -1,14 +1,22 @@
-use crate::{debug_println, type_defs::State, type_defs::EPS};
+use crate::{
+    debug_println,
+    type_defs::{PatternId, State, EPS},
+};
 use std::{
     collections::{HashMap, HashSet},
     fmt::Debug,
     hash::Hash,
 };
+/// Epsilon closures already computed, keyed by the state they start from
+pub type ClosureCache = HashMap<State, Vec<State>>;
+
 #[derive(Clone)]
 pub struct EFA<T> {
     pub(crate) automaton: HashMap<State, HashMap<Option<T>, Vec<State>>>,
     pub(crate) start: Option<State>,
     pub(crate) end: Option<State>,
+    // Final states of a union of patterns, with the pattern each one accepts
+    pub(crate) patterns: HashMap<State, PatternId>,
 }
 
 impl<T: Eq + Hash + Debug> EFA<T> {
@@ -17,6 +25,7 @@ impl<T: Eq + Hash + Debug> EFA<T> {
             automaton: HashMap::new(),
             start: None,
             end: None,
+            patterns: HashMap::new(),
         }
     }
     pub fn transition(&mut self, i_state: State, input: Option<T>, f_state: State) {
@@ -88,6 +97,13 @@ impl<T: Eq + Hash + Debug> EFA<T> {
     pub fn get_end(&self) -> Option<State> {
         self.end
     }
+    /// Final states with the pattern they accept. A single pattern has id 0.
+    pub fn pattern_ends(&self) -> HashMap<State, PatternId> {
+        if self.patterns.is_empty() {
+            return self.end.iter().map(|end| (*end, 0)).collect();
+        }
+        self.patterns.clone()
+    }
 
     pub fn get_possible_transitions(
         &self,
@@ -96,89 +112,147 @@ impl<T: Eq + Hash + Debug> EFA<T> {
         self.automaton.get(state)
     }
 
+    /// States reachable from `state` through epsilon transitions only, sorted
+    /// and without duplicates. Epsilon cycles, as built for `(a*)*`, are visited once.
     pub fn closure(&self, state: State) -> Vec<State> {
-        let mut result: Vec<State> = vec![state];
-        if let Some(transitions) = self.automaton.get(&state) {
-            if let Some(eps_transitions) = transitions.get(&None) {
-                for transition in eps_transitions {
-                    result.extend_from_slice(&self.closure(*transition));
+        let mut result: Vec<State> = Vec::new();
+        let mut visited: HashSet<State> = HashSet::new();
+        let mut work_list: Vec<State> = vec![state];
+        visited.insert(state);
+
+        while let Some(current) = work_list.pop() {
+            result.push(current);
+            let eps_transitions = self
+                .automaton
+                .get(&current)
+                .and_then(|transitions| transitions.get(&None));
+            for transition in eps_transitions.into_iter().flatten() {
+                if visited.insert(*transition) {
+                    work_list.push(*transition);
                 }
             }
         }
+        result.sort();
         result
     }
-}
-impl EFA<char> {
-    pub fn parse(&self, text: &str) -> Option<usize> {
-        let start = self.start.unwrap();
 
-        for (pos, _) in text.chars().enumerate() {
-            debug_println!("{:?} {:?}", &text[pos..], start);
-            if self.recursive_parse(&text[pos..], start).is_some() {
-                return Some(pos);
-            }
-        }
-        // Necessary for empty string that would be matched
-        // by "a*" where a is a token from the alphabet
-        if self.recursive_parse(text, start).is_some() {
-            return Some(0);
-        }
-        return None;
+    /// Same as `closure`, but remembers the result so that a subset construction
+    /// computes every closure only once.
+    pub fn closure_cached<'a>(&self, state: State, cache: &'a mut ClosureCache) -> &'a Vec<State> {
+        cache.entry(state).or_insert_with(|| self.closure(state))
     }
-    fn recursive_parse(&self, text: &str, state: State) -> Option<()> {
-        if state == self.end.unwrap() {
-            return Some(());
+
+    /// Union of the closures of `states`, sorted and without duplicates.
+    pub fn closure_of(
+        &self,
+        states: impl IntoIterator<Item = State>,
+        cache: &mut ClosureCache,
+    ) -> Vec<State> {
+        let mut result: Vec<State> = Vec::new();
+        for state in states {
+            result.extend_from_slice(self.closure_cached(state, cache));
         }
+        result.sort();
+        result.dedup();
+        result
+    }
+}
 
-        let possible_transitions = self
-            .automaton
-            .get(&state)
-            .expect("There should not be empty transitions except into final state");
-        let current_token = text.chars().next();
-
-        // Necessary for empty string that would be matched
-        // by "a*" where a is a token from the alphabet
-        if current_token.is_none() {
-            let eps_transition = possible_transitions.get(&None)?;
-            for transition in eps_transition {
-                debug_println!("  {} --{:?}--> {:2} {:?}", state, EPS, transition, text);
-
-                let result = self.recursive_parse(text, *transition);
-                if result.is_some() {
-                    return result;
+impl<T: Eq + Hash + Debug> Default for EFA<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Eq + Hash + Debug + Clone> EFA<T> {
+    /// Automaton of the reversed language: every transition is flipped and the
+    /// start and the end are swapped. Only meaningful with a single end.
+    pub fn reverse(&self) -> Self {
+        let mut efa: EFA<T> = EFA::new();
+        for (state, transitions) in self.automaton.iter() {
+            efa.automaton.entry(*state).or_default();
+            for (input, destinations) in transitions {
+                for destination in destinations {
+                    efa.transition(*destination, input.clone(), *state);
                 }
             }
-            return None;
         }
+        efa.start = self.end;
+        efa.end = self.start;
+        efa
+    }
 
-        let current_token = current_token.unwrap();
-
-        let direct_transitions = possible_transitions.get(&Some(current_token));
-        if direct_transitions.is_some() {
-            let direct_transition = direct_transitions.unwrap();
-            for transition in direct_transition {
-                debug_println!(
-                    "  {} --{:?}--> {:2} {}",
-                    state,
-                    current_token,
-                    transition,
-                    text
-                );
-
-                let result = self.recursive_parse(&text[1..], *transition);
-                if result.is_some() {
-                    return result;
+    /// Automaton accepting the union of `parts`, where the end of part `i`
+    /// accepts pattern `i`. States are renumbered so that the parts stay apart.
+    pub fn union(parts: &[EFA<T>]) -> Self {
+        let mut efa: EFA<T> = EFA::new();
+        let start: State = 0;
+        efa.empty_transition(start);
+        efa.set_start(start);
+
+        let mut offset: State = 1;
+        for (pattern, part) in parts.iter().enumerate() {
+            for (state, transitions) in part.automaton.iter() {
+                efa.automaton.entry(state + offset).or_default();
+                for (token, destinations) in transitions {
+                    for destination in destinations {
+                        efa.transition(state + offset, token.clone(), destination + offset);
+                    }
                 }
             }
-            return None;
+            if let Some(part_start) = part.start {
+                efa.transition(start, None, part_start + offset);
+            }
+            if let Some(end) = part.end {
+                efa.patterns.insert(end + offset, pattern);
+            }
+            offset += part.automaton.keys().max().map_or(0, |max| max + 1);
         }
+        efa
+    }
+}
+
+impl EFA<char> {
+    /// Position, in characters, of the leftmost match start. Walks the
+    /// automaton depth first from every start in turn. Whether the end can be
+    /// reached from a state at some offset does not depend on where the walk
+    /// started, so each pair is explored once over all the starts.
+    pub fn parse(&self, text: &str) -> Option<usize> {
+        let start = self.start?;
+        let end = self.end?;
+        let mut visited: HashSet<(State, usize)> = HashSet::new();
 
-        let eps_transition = possible_transitions.get(&None)?;
-        for transition in eps_transition {
-            debug_println!("  {} --{:?}--> {:2} {}", state, EPS, transition, text);
-            let result = self.recursive_parse(text, *transition);
-            if result.is_some() {
-                return result;
+        let starts = text.char_indices().map(|(offset, _)| offset);
+        for (pos, offset) in starts.chain([text.len()]).enumerate() {
+            let mut stack: Vec<(State, usize)> = vec![(start, offset)];
+            while let Some((state, offset)) = stack.pop() {
+                if state == end {
+                    return Some(pos);
+                }
+                // Also stops epsilon cycles such as the one of `(a*)*`
+                if !visited.insert((state, offset)) {
+                    continue;
+                }
+                let Some(transitions) = self.automaton.get(&state) else {
+                    continue;
+                };
+                for destination in transitions.get(&None).into_iter().flatten() {
+                    debug_println!("  {} --{:?}--> {:2} at {}", state, EPS, destination, offset);
+                    stack.push((*destination, offset));
+                }
+                let Some(token) = text[offset..].chars().next() else {
+                    continue;
+                };
+                for destination in transitions.get(&Some(token)).into_iter().flatten() {
+                    debug_println!(
+                        "  {} --{:?}--> {:2} at {}",
+                        state,
+                        token,
+                        destination,
+                        offset
+                    );
+                    stack.push((*destination, offset + token.len_utf8()));
+                }
             }
         }
         return None;
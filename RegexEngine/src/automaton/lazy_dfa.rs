@@ -0,0 +1,291 @@
+use crate::{
+    automaton::{
+        alphabet::CharClasses,
+        dense::{StateId, DEAD},
+        efa::{ClosureCache, EFA},
+        nfa::NFA,
+    },
+    type_defs::State,
+};
+use std::{collections::HashMap, mem::size_of, sync::Mutex};
+
+/// Default memory budget of the state cache, in bytes
+pub const DEFAULT_CACHE_BUDGET: usize = 1 << 20;
+/// Cache clears tolerated during one search before giving up on the DFA
+pub const DEFAULT_MAX_CLEARS: usize = 8;
+
+// Transition not computed yet
+const UNKNOWN: StateId = StateId::MAX;
+// Rough bookkeeping cost of a cached state besides its set and its row
+const STATE_OVERHEAD: usize = 64;
+
+/// Hybrid engine that runs the subset construction on demand.
+///
+/// Determinized states live in a cache bounded by a memory budget. Once the
+/// budget is exceeded the cache is thrown away and rebuilt from the current
+/// state. A search that has to clear it too often finishes on the NFA instead.
+///
+/// Both directions are unanchored: every subset also holds the start closure,
+/// so one forward pass sees every start at once. It stops adding starts after
+/// the first match, and reports where the runs still alive last match. A
+/// reverse pass from there back to the beginning finds the leftmost start.
+pub struct LazyDFA {
+    nfa: NFA<char>,
+    classes: CharClasses,
+    budget: usize,
+    max_clears: usize,
+    // Locked for a whole search, so that a parser can be shared across threads
+    searches: Mutex<Searches>,
+}
+
+struct Searches {
+    forward: Side,
+    reverse: Side,
+    // Clears of both caches during the current search
+    clears: usize,
+}
+
+// Automaton read in one direction, with the cache of its subsets
+struct Side {
+    efa: EFA<char>,
+    // Whether a subset that matches stops adding the start closure to its successors
+    stops_at_match: bool,
+    cache: Cache,
+}
+
+struct Cache {
+    sets: Vec<Vec<State>>,
+    ids: HashMap<Vec<State>, StateId>,
+    table: Vec<StateId>,
+    matching: Vec<bool>,
+    closures: ClosureCache,
+    start: Option<StateId>,
+    memory: usize,
+}
+
+// Member of every subset that still adds the start closure to its successors.
+// It sorts last and is never an EFA state
+const RESTART: State = State::MAX;
+
+impl LazyDFA {
+    pub fn new(efa: &EFA<char>, classes: CharClasses) -> Self {
+        Self::with_budget(efa, classes, DEFAULT_CACHE_BUDGET, DEFAULT_MAX_CLEARS)
+    }
+
+    /// Each direction gets its own cache of `budget` bytes, while
+    /// `max_clears` bounds the clears of both during one search.
+    pub fn with_budget(
+        efa: &EFA<char>,
+        classes: CharClasses,
+        budget: usize,
+        max_clears: usize,
+    ) -> Self {
+        let side = |efa: EFA<char>, stops_at_match: bool| {
+            let mut cache = Cache {
+                sets: Vec::new(),
+                ids: HashMap::new(),
+                table: Vec::new(),
+                matching: Vec::new(),
+                closures: HashMap::new(),
+                start: None,
+                memory: 0,
+            };
+            cache.reset(classes.len());
+            Side {
+                efa,
+                stops_at_match,
+                cache,
+            }
+        };
+        let searches = Searches {
+            forward: side(efa.clone(), true),
+            reverse: side(efa.reverse(), false),
+            clears: 0,
+        };
+        LazyDFA {
+            nfa: NFA::from_efa(efa).unwrap(),
+            classes,
+            budget,
+            max_clears,
+            searches: Mutex::new(searches),
+        }
+    }
+
+    /// Position, in characters, of the leftmost match start.
+    pub fn parse(&self, text: &str) -> Option<usize> {
+        let mut searches = self.searches.lock().unwrap();
+        let Searches {
+            forward,
+            reverse,
+            clears,
+        } = &mut *searches;
+        *clears = 0;
+
+        let start = self
+            .match_end(forward, clears, text)
+            .and_then(|end| match end {
+                Some(end) => self.leftmost_start(reverse, clears, &text[..end]),
+                None => Some(None),
+            });
+        match start {
+            Some(start) => start,
+            // The cache keeps thrashing, so the NFA is cheaper from here on
+            None => self.nfa.parse(text),
+        }
+    }
+
+    /// Number of times the last search had to clear a cache.
+    pub fn cache_clears(&self) -> usize {
+        self.searches.lock().unwrap().clears
+    }
+
+    /// Number of determinized states currently cached in both directions, the
+    /// dead states included.
+    pub fn cached_states(&self) -> usize {
+        let searches = self.searches.lock().unwrap();
+        searches.forward.cache.sets.len() + searches.reverse.cache.sets.len()
+    }
+
+    // Byte offset right after the last match of the runs alive at the earliest
+    // match end, which the leftmost match is one of. None when the search gave
+    // up on the cache
+    fn match_end(&self, side: &mut Side, clears: &mut usize, text: &str) -> Option<Option<usize>> {
+        let mut state = self.start_state(side, clears)?;
+        let mut end = None;
+        if side.cache.matching[state as usize] {
+            end = Some(0);
+        }
+        for (offset, token) in text.char_indices() {
+            state = self.next_state(side, clears, state, token)?;
+            if state == DEAD {
+                break;
+            }
+            if side.cache.matching[state as usize] {
+                end = Some(offset + token.len_utf8());
+            }
+        }
+        return Some(end);
+    }
+
+    // Position, in characters, of the leftmost start of a match within `text`,
+    // read backwards on the reversed automaton
+    fn leftmost_start(
+        &self,
+        side: &mut Side,
+        clears: &mut usize,
+        text: &str,
+    ) -> Option<Option<usize>> {
+        let mut state = self.start_state(side, clears)?;
+        let mut pos = text.chars().count();
+        let mut leftmost = None;
+        if side.cache.matching[state as usize] {
+            leftmost = Some(pos);
+        }
+        for token in text.chars().rev() {
+            state = self.next_state(side, clears, state, token)?;
+            pos -= 1;
+            if side.cache.matching[state as usize] {
+                leftmost = Some(pos);
+            }
+        }
+        return Some(leftmost);
+    }
+
+    fn start_state(&self, side: &mut Side, clears: &mut usize) -> Option<StateId> {
+        if let Some(start) = side.cache.start {
+            return Some(start);
+        }
+        let start = side.efa.get_start().unwrap();
+        let mut closure = side.efa.closure_of([start], &mut side.cache.closures);
+        closure.push(RESTART);
+        let start = self.add_state(side, clears, closure)?;
+        side.cache.start = Some(start);
+        Some(start)
+    }
+
+    fn next_state(
+        &self,
+        side: &mut Side,
+        clears: &mut usize,
+        state: StateId,
+        token: char,
+    ) -> Option<StateId> {
+        let class = self.classes.get(token);
+        let stride = self.classes.len();
+        let cached = side.cache.table[state as usize * stride + class];
+        if cached != UNKNOWN {
+            return Some(cached);
+        }
+
+        // Every member of a class behaves the same, so any of them will do
+        let current = &side.cache.sets[state as usize];
+        let restarts = current.last() == Some(&RESTART)
+            && !(side.stops_at_match && side.cache.matching[state as usize]);
+        let mut destinations: Vec<State> = Vec::new();
+        if let Some(representative) = self.classes.members(class).first() {
+            for source in current.iter() {
+                let transitions = side.efa.get_possible_transitions(source);
+                if let Some(targets) = transitions.and_then(|t| t.get(&Some(*representative))) {
+                    destinations.extend(targets.iter());
+                }
+            }
+        }
+        if restarts {
+            destinations.push(side.efa.get_start().unwrap());
+        }
+        let mut set = side.efa.closure_of(destinations, &mut side.cache.closures);
+        if restarts {
+            set.push(RESTART);
+        }
+
+        let before = *clears;
+        let next = self.add_state(side, clears, set)?;
+        // A clear invalidated `state`, so its row is gone
+        if *clears == before {
+            side.cache.table[state as usize * stride + class] = next;
+        }
+        Some(next)
+    }
+
+    fn add_state(&self, side: &mut Side, clears: &mut usize, set: Vec<State>) -> Option<StateId> {
+        let cache = &mut side.cache;
+        if let Some(id) = cache.ids.get(&set) {
+            return Some(*id);
+        }
+        let stride = self.classes.len();
+        let cost = set.len() * size_of::<State>() * 2 + stride * size_of::<StateId>();
+        if cache.memory + cost + STATE_OVERHEAD > self.budget {
+            if *clears >= self.max_clears {
+                return None;
+            }
+            *clears += 1;
+            cache.reset(stride);
+        }
+
+        let end = side.efa.get_end().unwrap();
+        let id = cache.sets.len() as StateId;
+        cache.matching.push(set.binary_search(&end).is_ok());
+        cache.ids.insert(set.clone(), id);
+        cache.sets.push(set);
+        cache.table.extend(std::iter::repeat_n(UNKNOWN, stride));
+        cache.memory += cost + STATE_OVERHEAD;
+        Some(id)
+    }
+}
+
+impl Cache {
+    // Forgets every state but the dead one, which always keeps id 0
+    fn reset(&mut self, stride: usize) {
+        self.sets.clear();
+        self.ids.clear();
+        self.table.clear();
+        self.matching.clear();
+        self.start = None;
+        self.memory = 0;
+
+        self.sets.push(Vec::new());
+        self.ids.insert(Vec::new(), DEAD);
+        self.table.extend(std::iter::repeat_n(DEAD, stride));
+        self.matching.push(false);
+    }
+}
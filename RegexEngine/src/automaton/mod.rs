@@ -1,3 +1,10 @@
+pub mod aho_corasick;
+pub mod alphabet;
+pub mod backtrack;
+pub mod dense;
 pub mod dfa;
 pub mod efa;
-// pub mod nfa;
+pub mod lazy_dfa;
+pub mod nfa;
+pub mod onepass;
+pub mod pikevm;
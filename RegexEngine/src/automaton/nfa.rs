@@ -1,14 +1,20 @@
-use crate::{automaton::efa::EFA, type_defs::State};
+use crate::{
+    automaton::efa::{ClosureCache, EFA},
+    type_defs::State,
+};
 use std::{
     collections::{HashMap, HashSet, VecDeque},
     fmt::Debug,
     hash::Hash,
 };
 
+/// Epsilon-free automaton. Every state is an EFA state that is either the start
+/// or the target of a symbol transition, and owns the symbol transitions of its closure.
+#[derive(Clone, Debug)]
 pub struct NFA<T> {
-    automaton: HashMap<Vec<State>, HashMap<T, Vec<State>>>,
-    start: Option<Vec<State>>,
-    end: Option<State>,
+    pub(crate) automaton: HashMap<State, HashMap<T, Vec<State>>>,
+    pub(crate) start: Option<State>,
+    pub(crate) end: Option<Vec<State>>,
 }
 
 impl<T: Eq + Hash + Debug + Clone> NFA<T> {
@@ -16,90 +22,74 @@ impl<T: Eq + Hash + Debug + Clone> NFA<T> {
         let start = efa.get_start()?;
         let end = efa.get_end()?;
 
-        let mut automaton: HashMap<Vec<State>, HashMap<T, Vec<State>>> = HashMap::new();
-        let mut closures: HashMap<State, Vec<State>> = HashMap::new();
+        let mut automaton: HashMap<State, HashMap<T, Vec<State>>> = HashMap::new();
+        let mut closures: ClosureCache = HashMap::new();
+        let mut end_states: Vec<State> = Vec::new();
 
-        let mut queue: VecDeque<Vec<State>> = VecDeque::new();
-        let mut visited: HashSet<Vec<State>> = HashSet::new();
+        let mut queue: VecDeque<State> = VecDeque::new();
+        let mut visited: HashSet<State> = HashSet::new();
 
-        let closure = efa.closure(start);
-        closures.insert(start, closure.clone());
-        queue.push_back(closure.clone());
+        queue.push_back(start);
+        visited.insert(start);
 
-        while let Some(current_states) = queue.pop_front() {
-            automaton.insert(current_states.clone(), HashMap::new());
-            let mut new_transitions: HashMap<T, Vec<State>> = HashMap::new();
+        while let Some(current_state) = queue.pop_front() {
+            let closure = efa.closure_cached(current_state, &mut closures);
+            if closure.binary_search(&end).is_ok() {
+                end_states.push(current_state);
+            }
 
-            // For each independent state
-            for state in current_states.iter() {
-                // Get its possible transitions
-                let transitions = efa.get_possible_transitions(&state);
+            let mut new_transitions: HashMap<T, Vec<State>> = HashMap::new();
+            // Pull the symbol transitions of the whole closure into this state
+            for state in closure.iter() {
+                let transitions = efa.get_possible_transitions(state);
                 if transitions.is_none() {
                     continue;
                 }
-                let transitions = transitions.unwrap();
-                let alphabet: Vec<_> = transitions.keys().collect();
-                // Using the tokens, for each individual transitions
-                for token_option in alphabet {
-                    if token_option.is_none() {
-                        continue;
+                for (token_option, destinations) in transitions.unwrap() {
+                    if let Some(token) = token_option {
+                        new_transitions
+                            .entry(token.clone())
+                            .or_default()
+                            .extend(destinations.iter());
                     }
-                    let token = token_option.clone().unwrap();
-                    // Get its destinations
-                    let destinations = transitions.get(&Some(token.clone())).unwrap();
-                    let mut new_destinations: HashSet<State> = HashSet::new();
-
-                    // Calculate the closure for each destination
-                    for destination in destinations {
-                        new_destinations.extend(
-                            closures
-                                .entry(*destination)
-                                .or_insert_with(|| {
-                                    let mut temp = efa.closure(*destination);
-                                    temp.sort();
-                                    temp
-                                })
-                                .iter(),
-                        );
-                    }
-
-                    // Generate the new possible transitions
-                    new_transitions
-                        .entry(token)
-                        .or_default()
-                        .extend(new_destinations.clone());
                 }
             }
 
-            // For each possible transition
-            for key in new_transitions.keys() {
-                let mut value = new_transitions.get(key).unwrap().clone();
-                value.sort();
-                // Check if it was visited. If not add it to the queue
-                if !visited.contains(&value.clone()) {
-                    queue.push_back(value.clone());
-                    visited.insert(value.clone());
+            for destinations in new_transitions.values_mut() {
+                destinations.sort();
+                destinations.dedup();
+                for destination in destinations.iter() {
+                    if visited.insert(*destination) {
+                        queue.push_back(*destination);
+                    }
                 }
-
-                automaton
-                    .get_mut(&current_states.clone())
-                    .unwrap()
-                    .entry(key.clone())
-                    .or_insert(value.clone());
             }
+            automaton.insert(current_state, new_transitions);
         }
 
+        end_states.sort();
         Some(NFA {
             automaton,
-            start: Some(closure),
-            end: Some(end),
+            start: Some(start),
+            end: Some(end_states),
         })
     }
 
+    pub fn get_start(&self) -> Option<State> {
+        self.start
+    }
+
+    pub fn is_final(&self, state: &State) -> bool {
+        self.end.as_ref().is_some_and(|end| end.contains(state))
+    }
+
+    pub fn get_possible_transitions(&self, state: &State) -> Option<&HashMap<T, Vec<State>>> {
+        self.automaton.get(state)
+    }
+
     pub fn print(&self) {
         let start = self
             .start
-            .as_ref()
             .map(|s| format!("{:?}", s))
             .unwrap_or_else(|| "N/A".to_string());
         let end = self
@@ -174,3 +164,46 @@ impl<T: Eq + Hash + Debug + Clone> NFA<T> {
         }
     }
 }
+
+impl NFA<char> {
+    /// Simulates all states at once. Each active state remembers the leftmost
+    /// position its thread started from, so one pass finds the leftmost match.
+    pub fn parse(&self, text: &str) -> Option<usize> {
+        let start = self.start?;
+        let mut current: HashMap<State, usize> = HashMap::new();
+        let mut best: Option<usize> = None;
+
+        for (pos, token) in text.chars().map(Some).chain([None]).enumerate() {
+            if best.is_none() {
+                current.entry(start).or_insert(pos);
+            }
+            for (state, origin) in current.iter() {
+                if self.is_final(state) && best.is_none_or(|best| *origin < best) {
+                    best = Some(*origin);
+                }
+            }
+            // Only threads that started before the best match can still improve it
+            if let Some(best) = best {
+                current.retain(|_, origin| *origin < best);
+            }
+            if current.is_empty() && best.is_some() {
+                break;
+            }
+
+            let Some(token) = token else { break };
+            let mut next: HashMap<State, usize> = HashMap::new();
+            for (state, origin) in current.iter() {
+                let destinations = self
+                    .automaton
+                    .get(state)
+                    .and_then(|transitions| transitions.get(&token));
+                for destination in destinations.into_iter().flatten() {
+                    let entry = next.entry(*destination).or_insert(*origin);
+                    *entry = (*entry).min(*origin);
+                }
+            }
+            current = next;
+        }
+        best
+    }
+}
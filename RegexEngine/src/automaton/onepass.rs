@@ -0,0 +1,204 @@
+use crate::thompson::{Inst, InstPtr, Program, Slots};
+use std::collections::{HashMap, VecDeque};
+
+/// DFA over the Thompson program of a one-pass pattern, where at every step
+/// the next character tells which single thread keeps going.
+///
+/// States are the instructions a thread can resume at. Each transition lists
+/// the capture slots to record before consuming its character, so a run fills
+/// the captures without tracking any other thread.
+#[derive(Clone, Debug)]
+pub struct OnePass {
+    states: Vec<OnePassState>,
+    slots: usize,
+}
+
+#[derive(Clone, Debug)]
+struct OnePassState {
+    transitions: HashMap<char, Step>,
+    // Slots recorded when matching here. Only tried if no transition applies
+    accept: Option<Vec<usize>>,
+}
+
+// Run of the one-pass DFA from one start, with the slots of its last match
+struct Thread {
+    state: usize,
+    start: usize,
+    slots: Slots,
+    matched: Option<Slots>,
+}
+
+#[derive(Clone, Debug)]
+struct Step {
+    target: usize,
+    saves: Vec<usize>,
+}
+
+impl OnePass {
+    /// None when the program is not one-pass: some character can be consumed
+    /// by two instructions of the same closure, or an instruction is reached
+    /// twice in it.
+    pub fn new(program: &Program) -> Option<Self> {
+        let mut ids: HashMap<InstPtr, usize> = HashMap::from([(program.start, 0)]);
+        let mut states: Vec<OnePassState> = Vec::new();
+        let mut queue: VecDeque<InstPtr> = VecDeque::from([program.start]);
+
+        while let Some(pc) = queue.pop_front() {
+            let mut state = OnePassState {
+                transitions: HashMap::new(),
+                accept: None,
+            };
+            let mut seen = vec![false; program.insts.len()];
+            // Depth first in priority order, with the slots saved on the way
+            let mut stack: Vec<(InstPtr, Vec<usize>)> = vec![(pc, Vec::new())];
+            while let Some((pc, mut saves)) = stack.pop() {
+                if seen[pc] {
+                    return None;
+                }
+                seen[pc] = true;
+                match program.insts[pc] {
+                    Inst::Split(first, second) => {
+                        stack.push((second, saves.clone()));
+                        stack.push((first, saves));
+                    }
+                    Inst::Save(slot, target) => {
+                        saves.push(slot);
+                        stack.push((target, saves));
+                    }
+                    Inst::Char(ch, target) => {
+                        let next_id = ids.len();
+                        let target = *ids.entry(target).or_insert_with(|| {
+                            queue.push_back(target);
+                            next_id
+                        });
+                        let step = Step { target, saves };
+                        if state.transitions.insert(ch, step).is_some() {
+                            return None;
+                        }
+                    }
+                    Inst::Match => {
+                        // Lower priority branches are never taken
+                        state.accept = Some(saves);
+                        break;
+                    }
+                }
+            }
+            states.push(state);
+        }
+
+        Some(OnePass {
+            states,
+            slots: program.slots,
+        })
+    }
+
+    pub fn is_one_pass(program: &Program) -> bool {
+        Self::new(program).is_some()
+    }
+
+    pub fn state_count(&self) -> usize {
+        self.states.len()
+    }
+
+    /// Capture slots of the leftmost-first match, in a single pass. A thread
+    /// starts at every position until some thread matches; two threads in the
+    /// same state share their future, so the later one is dropped unless it
+    /// is the only one of them to have matched already.
+    pub fn captures(&self, text: &str) -> Option<Slots> {
+        let mut threads: Vec<Thread> = Vec::new();
+        // Earliest start of a thread that died after matching, with its slots
+        let mut best: Option<(usize, Slots)> = None;
+
+        for (pos, token) in text.chars().map(Some).chain([None]).enumerate() {
+            if best.is_none() && threads.iter().all(|thread| thread.matched.is_none()) {
+                threads.push(Thread {
+                    state: 0,
+                    start: pos,
+                    slots: vec![None; self.slots],
+                    matched: None,
+                });
+            }
+
+            // Threads are sorted by start, which `next` preserves
+            let mut next: Vec<Thread> = Vec::with_capacity(threads.len());
+            for mut thread in threads {
+                let current = &self.states[thread.state];
+                if let Some(saves) = &current.accept {
+                    let mut matched = thread.slots.clone();
+                    for slot in saves {
+                        matched[*slot] = Some(pos);
+                    }
+                    thread.matched = Some(matched);
+                }
+                let step = token.and_then(|token| current.transitions.get(&token));
+                let Some(step) = step else {
+                    if let Some(matched) = thread.matched {
+                        if best.as_ref().is_none_or(|(start, _)| thread.start < *start) {
+                            best = Some((thread.start, matched));
+                        }
+                    }
+                    continue;
+                };
+                for slot in step.saves.iter() {
+                    thread.slots[*slot] = Some(pos);
+                }
+                thread.state = step.target;
+                let shadowed = next.iter().any(|earlier| {
+                    earlier.state == thread.state
+                        && (earlier.matched.is_some() || thread.matched.is_none())
+                });
+                if !shadowed {
+                    next.push(thread);
+                }
+            }
+
+            // Only threads that started no later than a match can still win
+            let matched_start = next
+                .iter()
+                .filter(|thread| thread.matched.is_some())
+                .map(|thread| thread.start)
+                .chain(best.as_ref().map(|(start, _)| *start))
+                .min();
+            if let Some(matched_start) = matched_start {
+                next.retain(|thread| thread.start <= matched_start);
+            }
+            threads = next;
+            if threads.is_empty() && best.is_some() {
+                break;
+            }
+        }
+        return best.map(|(_, slots)| slots);
+    }
+
+    /// Capture slots of the leftmost-first match starting at character `start`.
+    pub fn captures_at(&self, text: &str, start: usize) -> Option<Slots> {
+        let mut slots: Slots = vec![None; self.slots];
+        // Match to report if the thread dies further on
+        let mut fallback: Option<Slots> = None;
+        let mut tokens = text.chars().skip(start);
+        let mut state = 0;
+        let mut pos = start;
+
+        loop {
+            let current = &self.states[state];
+            if let Some(saves) = &current.accept {
+                let mut matched = slots.clone();
+                for slot in saves {
+                    matched[*slot] = Some(pos);
+                }
+                fallback = Some(matched);
+            }
+            let step = tokens
+                .next()
+                .and_then(|token| current.transitions.get(&token));
+            let Some(step) = step else {
+                return fallback;
+            };
+            for slot in step.saves.iter() {
+                slots[*slot] = Some(pos);
+            }
+            state = step.target;
+            pos += 1;
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use crate::thompson::{Inst, InstPtr, Program, Slots};
+
+/// Simulates the Thompson program with one thread per instruction, advancing
+/// every thread in lockstep over the text.
+///
+/// Threads are kept in priority order, so the result is the leftmost match
+/// that a backtracker would find first, along with its captures.
+#[derive(Clone, Debug)]
+pub struct PikeVM {
+    program: Program,
+}
+
+// Threads waiting on a `Char` or `Match`, in priority order
+struct Threads {
+    list: Vec<(InstPtr, Slots)>,
+    seen: Vec<bool>,
+}
+
+impl PikeVM {
+    pub fn new(program: &Program) -> Self {
+        PikeVM {
+            program: program.clone(),
+        }
+    }
+
+    /// Capture slots of the leftmost-first match.
+    pub fn captures(&self, text: &str) -> Option<Slots> {
+        let size = self.program.insts.len();
+        let mut current = Threads::new(size);
+        let mut next = Threads::new(size);
+        let mut matched: Option<Slots> = None;
+
+        for (pos, token) in text.chars().map(Some).chain([None]).enumerate() {
+            // A thread starting here ranks below every thread already running
+            if matched.is_none() {
+                let slots = vec![None; self.program.slots];
+                self.add_thread(&mut current, self.program.start, pos, slots);
+            }
+            if current.list.is_empty() {
+                break;
+            }
+
+            for (pc, slots) in current.list.drain(..) {
+                match self.program.insts[pc] {
+                    Inst::Char(ch, target) => {
+                        if token == Some(ch) {
+                            self.add_thread(&mut next, target, pos + 1, slots);
+                        }
+                    }
+                    Inst::Match => {
+                        // Lower priority threads can no longer win
+                        matched = Some(slots);
+                        break;
+                    }
+                    _ => unreachable!("Only Char and Match threads are queued"),
+                }
+            }
+            current.clear();
+            std::mem::swap(&mut current, &mut next);
+            if token.is_none() {
+                break;
+            }
+        }
+        return matched;
+    }
+
+    // Follows Split and Save instructions depth first, keeping the priority order
+    fn add_thread(&self, threads: &mut Threads, pc: InstPtr, pos: usize, slots: Slots) {
+        let mut stack: Vec<(InstPtr, Slots)> = vec![(pc, slots)];
+        while let Some((pc, mut slots)) = stack.pop() {
+            if threads.seen[pc] {
+                continue;
+            }
+            threads.seen[pc] = true;
+            match self.program.insts[pc] {
+                Inst::Split(first, second) => {
+                    stack.push((second, slots.clone()));
+                    stack.push((first, slots));
+                }
+                Inst::Save(slot, target) => {
+                    slots[slot] = Some(pos);
+                    stack.push((target, slots));
+                }
+                Inst::Char(..) | Inst::Match => threads.list.push((pc, slots)),
+            }
+        }
+    }
+}
+
+impl Threads {
+    fn new(size: usize) -> Self {
+        Threads {
+            list: Vec::new(),
+            seen: vec![false; size],
+        }
+    }
+
+    fn clear(&mut self) {
+        self.list.clear();
+        self.seen.iter_mut().for_each(|seen| *seen = false);
+    }
+}
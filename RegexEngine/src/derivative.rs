@@ -0,0 +1,265 @@
+use crate::automaton::dfa::DFA;
+use crate::regex_parser::{parse_boolean_ast, ParseError};
+use crate::state_generator::StateGenerator;
+use crate::tree::Node;
+use crate::type_defs::{PatternId, State};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+
+/// A pattern as an algebraic term, matched through Brzozowski derivatives.
+///
+/// The derivative of a term by a character matches the rest of every string
+/// the term matches that starts with that character. Terms are only built by
+/// the constructors below, which keep them normalized: alternations and
+/// intersections are flattened, sorted and without duplicates, so that
+/// derivatives taken over and over only produce finitely many terms.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Term {
+    /// Matches nothing
+    Empty,
+    /// Matches the empty string only
+    Epsilon,
+    Char(char),
+    Concat(Box<Term>, Box<Term>),
+    /// At least two members, sorted
+    Or(Vec<Term>),
+    /// At least two members, sorted
+    And(Vec<Term>),
+    Star(Box<Term>),
+    /// Every string the inner term does not match
+    Not(Box<Term>),
+}
+
+impl Term {
+    /// Term of `pattern`, which may use `&` and `~` on top of the usual syntax.
+    pub fn parse(pattern: &str) -> Result<Self, ParseError> {
+        Ok(Self::from_ast(&parse_boolean_ast(pattern)?))
+    }
+
+    /// Capture groups only matter to the Thompson engines, so they are dropped.
+    pub fn from_ast(node: &Node<char>) -> Self {
+        if node.is_epsilon() {
+            return Term::Epsilon;
+        }
+        if node.is_leaf() {
+            return Term::Char(node.value);
+        }
+        let left = Self::from_ast(node.left.as_ref().unwrap());
+        let right = node.right.as_deref().map(Self::from_ast);
+        match node.value {
+            '·' => Self::concat(left, right.unwrap()),
+            '|' => Self::or(vec![left, right.unwrap()]),
+            '&' => Self::and(vec![left, right.unwrap()]),
+            '*' => Self::star(left),
+            '~' => Self::complement(left),
+            '(' => left,
+            _ => panic!("Unknown operator {}", node.value),
+        }
+    }
+
+    pub fn concat(left: Term, right: Term) -> Self {
+        match (left, right) {
+            (Term::Empty, _) | (_, Term::Empty) => Term::Empty,
+            (Term::Epsilon, term) | (term, Term::Epsilon) => term,
+            // Kept right nested, so that grouping never tells two terms apart
+            (Term::Concat(first, second), right) => {
+                Term::Concat(first, Box::new(Self::concat(*second, right)))
+            }
+            (left, right) => Term::Concat(Box::new(left), Box::new(right)),
+        }
+    }
+
+    pub fn or(terms: Vec<Term>) -> Self {
+        let mut members: BTreeSet<Term> = BTreeSet::new();
+        for term in terms {
+            match term {
+                Term::Or(inner) => members.extend(inner),
+                Term::Empty => {}
+                term => {
+                    members.insert(term);
+                }
+            }
+        }
+        if members.contains(&Self::universal()) {
+            return Self::universal();
+        }
+        match members.len() {
+            0 => Term::Empty,
+            1 => members.pop_first().unwrap(),
+            _ => Term::Or(members.into_iter().collect()),
+        }
+    }
+
+    pub fn and(terms: Vec<Term>) -> Self {
+        let mut members: BTreeSet<Term> = BTreeSet::new();
+        for term in terms {
+            match term {
+                Term::And(inner) => members.extend(inner),
+                Term::Empty => return Term::Empty,
+                term if term == Self::universal() => {}
+                term => {
+                    members.insert(term);
+                }
+            }
+        }
+        match members.len() {
+            0 => Self::universal(),
+            1 => members.pop_first().unwrap(),
+            _ => Term::And(members.into_iter().collect()),
+        }
+    }
+
+    pub fn star(body: Term) -> Self {
+        match body {
+            Term::Empty | Term::Epsilon => Term::Epsilon,
+            Term::Star(inner) => Term::Star(inner),
+            body => Term::Star(Box::new(body)),
+        }
+    }
+
+    pub fn complement(body: Term) -> Self {
+        match body {
+            Term::Not(inner) => *inner,
+            body => Term::Not(Box::new(body)),
+        }
+    }
+
+    /// Matches every string.
+    pub fn universal() -> Self {
+        Term::Not(Box::new(Term::Empty))
+    }
+
+    /// The empty string is matched.
+    pub fn nullable(&self) -> bool {
+        match self {
+            Term::Empty | Term::Char(_) => false,
+            Term::Epsilon | Term::Star(_) => true,
+            Term::Concat(left, right) => left.nullable() && right.nullable(),
+            Term::Or(members) => members.iter().any(Term::nullable),
+            Term::And(members) => members.iter().all(Term::nullable),
+            Term::Not(body) => !body.nullable(),
+        }
+    }
+
+    pub fn derivative(&self, token: char) -> Self {
+        self.derive(Some(token))
+    }
+
+    // `None` stands for any character the term does not mention, which all
+    // have the same derivative
+    fn derive(&self, token: Option<char>) -> Self {
+        match self {
+            Term::Empty | Term::Epsilon => Term::Empty,
+            Term::Char(expected) => match token == Some(*expected) {
+                true => Term::Epsilon,
+                false => Term::Empty,
+            },
+            Term::Concat(left, right) => {
+                let through_left = Self::concat(left.derive(token), (**right).clone());
+                match left.nullable() {
+                    true => Self::or(vec![through_left, right.derive(token)]),
+                    false => through_left,
+                }
+            }
+            Term::Or(members) => Self::or(members.iter().map(|term| term.derive(token)).collect()),
+            Term::And(members) => {
+                Self::and(members.iter().map(|term| term.derive(token)).collect())
+            }
+            Term::Star(body) => Self::concat(body.derive(token), self.clone()),
+            Term::Not(body) => Self::complement(body.derive(token)),
+        }
+    }
+
+    /// Characters the term mentions, sorted.
+    pub fn alphabet(&self) -> Vec<char> {
+        let mut alphabet: BTreeSet<char> = BTreeSet::new();
+        self.collect_alphabet(&mut alphabet);
+        alphabet.into_iter().collect()
+    }
+
+    fn collect_alphabet(&self, alphabet: &mut BTreeSet<char>) {
+        match self {
+            Term::Empty | Term::Epsilon => {}
+            Term::Char(token) => {
+                alphabet.insert(*token);
+            }
+            Term::Concat(left, right) => {
+                left.collect_alphabet(alphabet);
+                right.collect_alphabet(alphabet);
+            }
+            Term::Or(members) | Term::And(members) => {
+                for term in members {
+                    term.collect_alphabet(alphabet);
+                }
+            }
+            Term::Star(body) | Term::Not(body) => body.collect_alphabet(alphabet),
+        }
+    }
+
+    /// Whether the whole of `text` is matched, taking one derivative per
+    /// character without building any automaton.
+    pub fn is_full_match(&self, text: &str) -> bool {
+        let mut term = self.clone();
+        for token in text.chars() {
+            term = term.derivative(token);
+            if term == Term::Empty {
+                return false;
+            }
+        }
+        term.nullable()
+    }
+
+    /// Minimal DFA whose states are the derivatives of the term. Characters
+    /// the term does not mention follow the default transitions.
+    pub fn to_dfa(&self) -> DFA<char> {
+        let alphabet = self.alphabet();
+        let mut automaton: HashMap<State, HashMap<char, State>> = HashMap::new();
+        let mut others: HashMap<State, State> = HashMap::new();
+        let mut end: HashMap<State, Vec<PatternId>> = HashMap::new();
+
+        let mut queue: VecDeque<State> = VecDeque::new();
+        let mut visited: HashSet<State> = HashSet::new();
+        let mut state_generator: StateGenerator<Term, State> = StateGenerator::new();
+
+        let start = state_generator.generate_for(self);
+        queue.push_back(start);
+        visited.insert(start);
+        while let Some(current_state) = queue.pop_front() {
+            let term = state_generator.get_value(&current_state).unwrap();
+            if term.nullable() {
+                end.insert(current_state, vec![0]);
+            }
+            let mut transitions: HashMap<char, State> = HashMap::new();
+            // Transitions into the empty term are left out, as in a partial DFA,
+            // unless a live default transition would take them over
+            let default_lives = term.derive(None) != Term::Empty;
+            let tokens = alphabet.iter().map(|token| Some(*token)).chain([None]);
+            for token in tokens {
+                let derivative = term.derive(token);
+                if derivative == Term::Empty && !default_lives {
+                    continue;
+                }
+                let new_state = state_generator.generate_for(&derivative);
+                if visited.insert(new_state) {
+                    queue.push_back(new_state);
+                }
+                match token {
+                    Some(token) => {
+                        transitions.insert(token, new_state);
+                    }
+                    None => {
+                        others.insert(current_state, new_state);
+                    }
+                }
+            }
+            automaton.insert(current_state, transitions);
+        }
+
+        let dfa = DFA {
+            automaton,
+            start: Some(start),
+            end: Some(end),
+            others,
+        };
+        DFA::minimize_from(dfa).unwrap()
+    }
+}
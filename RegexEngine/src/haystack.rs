@@ -0,0 +1,89 @@
+/// Text that the automata can walk in both directions.
+///
+/// Offsets are in bytes and always fall on character boundaries. Byte slices
+/// are decoded as UTF-8; every byte of an invalid sequence reads as `None`, the
+/// same way in both directions, so that no character of a pattern matches it.
+pub trait Haystack {
+    fn byte_len(&self) -> usize;
+
+    /// Character starting at `offset`, with its width in bytes.
+    fn char_at(&self, offset: usize) -> Option<(Option<char>, usize)>;
+
+    /// Character ending right before `offset`, with its width in bytes.
+    fn char_before(&self, offset: usize) -> Option<(Option<char>, usize)>;
+}
+
+impl Haystack for str {
+    fn byte_len(&self) -> usize {
+        self.len()
+    }
+
+    fn char_at(&self, offset: usize) -> Option<(Option<char>, usize)> {
+        let token = self[offset..].chars().next()?;
+        Some((Some(token), token.len_utf8()))
+    }
+
+    fn char_before(&self, offset: usize) -> Option<(Option<char>, usize)> {
+        let token = self[..offset].chars().next_back()?;
+        Some((Some(token), token.len_utf8()))
+    }
+}
+
+impl Haystack for [u8] {
+    fn byte_len(&self) -> usize {
+        self.len()
+    }
+
+    fn char_at(&self, offset: usize) -> Option<(Option<char>, usize)> {
+        if offset >= self.len() {
+            return None;
+        }
+        Some(decode_utf8(&self[offset..]).map_or((None, 1), |(token, width)| (Some(token), width)))
+    }
+
+    fn char_before(&self, offset: usize) -> Option<(Option<char>, usize)> {
+        if offset == 0 {
+            return None;
+        }
+        // A valid sequence cannot contain the lead byte of another one, so at
+        // most one width fits, and then it agrees with the forward decoding
+        for width in (2..=offset.min(4)).rev() {
+            if let Some((token, decoded)) = decode_utf8(&self[offset - width..offset]) {
+                if decoded == width {
+                    return Some((Some(token), width));
+                }
+            }
+        }
+        let last = decode_utf8(&self[offset - 1..offset]);
+        Some(last.map_or((None, 1), |(token, width)| (Some(token), width)))
+    }
+}
+
+/// Decodes a haystack into text, with the byte offset of every character and
+/// of the end. Invalid bytes become `invalid`, which callers pick among the
+/// characters their pattern never mentions.
+pub fn decode<H: Haystack + ?Sized>(haystack: &H, invalid: char) -> (String, Vec<usize>) {
+    let mut text = String::new();
+    let mut offsets = Vec::new();
+    let mut offset = 0;
+    while let Some((token, width)) = haystack.char_at(offset) {
+        text.push(token.unwrap_or(invalid));
+        offsets.push(offset);
+        offset += width;
+    }
+    offsets.push(offset);
+    (text, offsets)
+}
+
+// First character of `bytes` if it starts with a valid sequence
+fn decode_utf8(bytes: &[u8]) -> Option<(char, usize)> {
+    let width = match bytes.first()? {
+        0x00..=0x7F => 1,
+        0xC2..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF4 => 4,
+        _ => return None,
+    };
+    let sequence = std::str::from_utf8(bytes.get(..width)?).ok()?;
+    Some((sequence.chars().next()?, width))
+}
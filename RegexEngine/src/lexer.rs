@@ -0,0 +1,151 @@
+use crate::automaton::dense::{DenseDFA, StateId, DEAD};
+use crate::regex_parser::{compile_union, parse_ast};
+use crate::tree::Node;
+use std::collections::HashSet;
+
+/// Token recognised by a `Lexer`. The span is in characters, end excluded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token<K> {
+    pub kind: K,
+    pub span: (usize, usize),
+}
+
+/// Stretch of input that no rule matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LexError {
+    pub span: (usize, usize),
+}
+
+/// Tokenizer compiled from ordered `(kind, pattern)` rules.
+///
+/// Every rule ends up in one DFA whose final states carry the rules they
+/// accept. Each token is the longest prefix some rule matches, the earliest
+/// rule winning when several match the same length.
+pub struct Lexer<K> {
+    kinds: Vec<K>,
+    automaton: DenseDFA,
+    skip: Vec<K>,
+}
+
+impl<K: Clone + PartialEq> Lexer<K> {
+    pub fn new(rules: &[(K, &str)]) -> Self {
+        let trees: Vec<Node<char>> = rules
+            .iter()
+            .map(|(_, pattern)| {
+                parse_ast(pattern)
+                    .unwrap_or_else(|error| panic!("Invalid pattern {:?}: {}", pattern, error))
+            })
+            .collect();
+        let (dfa, classes) = compile_union(&trees);
+        Lexer {
+            kinds: rules.iter().map(|(kind, _)| kind.clone()).collect(),
+            automaton: DenseDFA::new(&dfa, classes),
+            skip: Vec::new(),
+        }
+    }
+
+    /// Tokens of these kinds are matched but never yielded, e.g. whitespace.
+    pub fn skip(mut self, kinds: &[K]) -> Self {
+        self.skip.extend(kinds.iter().cloned());
+        self
+    }
+
+    pub fn tokenize<'a>(&'a self, text: &'a str) -> Tokens<'a, K> {
+        Tokens {
+            lexer: self,
+            text,
+            offset: 0,
+            pos: 0,
+            failed: HashSet::new(),
+        }
+    }
+
+    // Rule, length in characters and length in bytes of the longest non
+    // empty prefix of `text[from..]` that some rule matches.
+    //
+    // `failed` holds the states that cannot reach a final state from the byte
+    // offset they are paired with. Every run adds the pairs it went through
+    // after its last final state, and stops at any pair already there, so
+    // that no pair is walked twice over a whole text.
+    fn longest_match(
+        &self,
+        text: &str,
+        from: usize,
+        failed: &mut HashSet<(StateId, usize)>,
+    ) -> Option<(usize, usize, usize)> {
+        let mut state = self.automaton.start;
+        let mut longest = None;
+        let mut length = 0;
+        let mut trail: Vec<(StateId, usize)> = Vec::new();
+        for (offset, token) in text[from..].char_indices() {
+            let end = offset + token.len_utf8();
+            state = self.automaton.next_state(state, token);
+            if state == DEAD || failed.contains(&(state, from + end)) {
+                break;
+            }
+            length += 1;
+            if let Some(rule) = self.automaton.patterns_of(state).first() {
+                longest = Some((*rule, length, end));
+                trail.clear();
+            } else {
+                trail.push((state, from + end));
+            }
+        }
+        failed.extend(trail);
+        return longest;
+    }
+}
+
+/// Tokens of a text, in order. Unmatched input is reported as one error per
+/// stretch, after which lexing resumes at the next position where a rule matches.
+pub struct Tokens<'a, K> {
+    lexer: &'a Lexer<K>,
+    text: &'a str,
+    // Where the next token starts, in bytes and in characters
+    offset: usize,
+    pos: usize,
+    failed: HashSet<(StateId, usize)>,
+}
+
+impl<K: Clone + PartialEq> Iterator for Tokens<'_, K> {
+    type Item = Result<Token<K>, LexError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let first = self.text[self.offset..].chars().next()?;
+            let start = self.pos;
+
+            let longest = self
+                .lexer
+                .longest_match(self.text, self.offset, &mut self.failed);
+            let Some((rule, length, bytes)) = longest else {
+                self.offset += first.len_utf8();
+                self.pos += 1;
+                while let Some(token) = self.text[self.offset..].chars().next() {
+                    let resumes =
+                        self.lexer
+                            .longest_match(self.text, self.offset, &mut self.failed);
+                    if resumes.is_some() {
+                        break;
+                    }
+                    self.offset += token.len_utf8();
+                    self.pos += 1;
+                }
+                return Some(Err(LexError {
+                    span: (start, self.pos),
+                }));
+            };
+
+            self.offset += bytes;
+            self.pos += length;
+            let kind = &self.lexer.kinds[rule];
+            if self.lexer.skip.contains(kind) {
+                continue;
+            }
+            return Some(Ok(Token {
+                kind: kind.clone(),
+                span: (start, self.pos),
+            }));
+        }
+    }
+}
@@ -0,0 +1,17 @@
+#![allow(clippy::needless_return, clippy::upper_case_acronyms)]
+
+pub mod automaton;
+pub mod derivative;
+pub mod haystack;
+pub mod lexer;
+mod macros;
+pub mod regex_parser;
+pub mod state_generator;
+pub mod thompson;
+pub mod tree;
+pub mod type_defs;
+
+pub use crate::regex_parser::{ParseError, RegexParser, RegexSet};
+
+#[cfg(test)]
+mod tests;
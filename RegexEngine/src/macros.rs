@@ -1,10 +1,11 @@
-pub const DEBUG: bool = true;
+// Traces of the automata walks, only printed when built with `--features debug`
+pub const DEBUG: bool = cfg!(feature = "debug");
 
 // Macro for conditional printing
 #[macro_export]
 macro_rules! debug_println {
     ($($arg:tt)*) => {
-        if crate::macros::DEBUG {
+        if $crate::macros::DEBUG {
             println!($($arg)*);
         }
     };
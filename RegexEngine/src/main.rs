@@ -1,14 +1,4 @@
-mod automaton;
-mod macros;
-mod regex_parser;
-mod state_generator;
-mod tree;
-mod type_defs;
-
-#[cfg(test)]
-mod tests;
-
-use crate::regex_parser::RegexParser;
+use regex_engine::RegexParser;
 
 fn main() {
     let parser = RegexParser::from("(a|b)*");
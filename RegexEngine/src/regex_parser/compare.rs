@@ -0,0 +1,51 @@
+use super::GenericRegexParser;
+use crate::automaton::dfa::DFA;
+
+/// Shortest string that tells two patterns apart.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Counterexample {
+    pub text: String,
+    /// Whether the pattern the comparison was called on accepts `text`.
+    /// The other pattern does the opposite
+    pub accepted_by_self: bool,
+}
+
+impl GenericRegexParser<char> {
+    /// Minimal DFA of the strings the whole pattern matches, built on the spot
+    /// when the engine did not need one.
+    pub fn language(&self) -> DFA<char> {
+        if let Some(automaton) = &self.automaton {
+            return automaton.clone();
+        }
+        let dfa = DFA::from_efa(&self.efa).unwrap();
+        return DFA::minimize_from(dfa).unwrap();
+    }
+
+    /// Both patterns match exactly the same strings, or else the shortest
+    /// string only one of them matches.
+    pub fn equivalent(&self, other: &Self) -> Result<(), Counterexample> {
+        self.compare(other, |left, right| left != right)
+    }
+
+    /// Every string this pattern matches is matched by `other` too, or else
+    /// the shortest one that is not.
+    pub fn is_subset_of(&self, other: &Self) -> Result<(), Counterexample> {
+        self.compare(other, |left, right| left && !right)
+    }
+
+    fn compare(
+        &self,
+        other: &Self,
+        differs: impl Fn(bool, bool) -> bool,
+    ) -> Result<(), Counterexample> {
+        let language = self.language();
+        let witness = DFA::shortest_witness(&language, &other.language(), differs);
+        match witness {
+            None => Ok(()),
+            Some(text) => Err(Counterexample {
+                accepted_by_self: language.is_full_match(&text),
+                text,
+            }),
+        }
+    }
+}
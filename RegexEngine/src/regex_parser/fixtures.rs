@@ -0,0 +1,19 @@
+use super::GenericRegexParser;
+use crate::automaton::dfa::SplitMix64;
+
+impl GenericRegexParser<char> {
+    /// The first `count` strings the whole pattern matches, shortest first.
+    pub fn examples(&self, count: usize) -> Vec<String> {
+        self.language().strings().take(count).collect()
+    }
+
+    /// `count` strings of at most `max_length` characters that the whole
+    /// pattern matches, drawn uniformly from `seed`. Empty when there is none.
+    pub fn fixtures(&self, count: usize, max_length: usize, seed: u64) -> Vec<String> {
+        let language = self.language();
+        let mut rng = SplitMix64::new(seed);
+        (0..count)
+            .map_while(|_| language.sample(max_length, &mut rng))
+            .collect()
+    }
+}
@@ -0,0 +1,132 @@
+use crate::tree::Node;
+
+/// Strings that every match of a pattern contains. Empty when nothing is known.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Literals {
+    /// Every match starts with it
+    pub prefix: String,
+    /// Every match ends with it
+    pub suffix: String,
+    /// Longest string found inside every match
+    pub inner: String,
+    // The whole language when it is a single string
+    exact: Option<String>,
+}
+
+impl Literals {
+    pub fn of(node: &Node<char>) -> Self {
+        if node.is_leaf() || node.is_epsilon() {
+            // The empty string is a literal too, only a shorter one
+            let literal = match node.is_epsilon() {
+                true => String::new(),
+                false => node.value.to_string(),
+            };
+            return Literals {
+                prefix: literal.clone(),
+                suffix: literal.clone(),
+                inner: literal.clone(),
+                exact: Some(literal),
+            };
+        }
+
+        match node.value {
+            '·' => {
+                let left = Self::of(node.left.as_ref().unwrap());
+                let right = Self::of(node.right.as_ref().unwrap());
+                let prefix = match &left.exact {
+                    Some(exact) => exact.clone() + &right.prefix,
+                    None => left.prefix.clone(),
+                };
+                let suffix = match &right.exact {
+                    Some(exact) => left.suffix.clone() + exact,
+                    None => right.suffix.clone(),
+                };
+                // The end of the left side always touches the start of the right one
+                let junction = left.suffix.clone() + &right.prefix;
+                let exact = left.exact.zip(right.exact).map(|(l, r)| l + &r);
+                let inner = longest([
+                    left.inner,
+                    right.inner,
+                    junction,
+                    prefix.clone(),
+                    suffix.clone(),
+                ]);
+                Literals {
+                    prefix,
+                    suffix,
+                    inner,
+                    exact,
+                }
+            }
+            '|' => {
+                let left = Self::of(node.left.as_ref().unwrap());
+                let right = Self::of(node.right.as_ref().unwrap());
+                if left == right {
+                    return left;
+                }
+                let prefix = common_prefix(&left.prefix, &right.prefix);
+                let suffix = common_suffix(&left.suffix, &right.suffix);
+                let inner = longest([prefix.clone(), suffix.clone()]);
+                Literals {
+                    prefix,
+                    suffix,
+                    inner,
+                    exact: None,
+                }
+            }
+            '(' => Self::of(node.left.as_ref().unwrap()),
+            // The body may be skipped, so nothing is required
+            _ => Literals::default(),
+        }
+    }
+
+    /// The only string the pattern matches, if there is one.
+    pub fn exact(&self) -> Option<&str> {
+        self.exact.as_deref()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+}
+
+/// Branches of an alternation of plain literals, in priority order.
+pub fn alternation_of(node: &Node<char>) -> Option<Vec<String>> {
+    if node.value == '|' && !node.is_leaf() {
+        let mut branches = alternation_of(node.left.as_ref()?)?;
+        branches.extend(alternation_of(node.right.as_ref()?)?);
+        return Some(branches);
+    }
+    let literals = Literals::of(node);
+    return Some(vec![literals.exact()?.to_string()]);
+}
+
+fn longest<const N: usize>(candidates: [String; N]) -> String {
+    let mut best = String::new();
+    for candidate in candidates {
+        if candidate.chars().count() > best.chars().count() {
+            best = candidate;
+        }
+    }
+    best
+}
+
+fn common_prefix(first: &str, second: &str) -> String {
+    first
+        .chars()
+        .zip(second.chars())
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a)
+        .collect()
+}
+
+fn common_suffix(first: &str, second: &str) -> String {
+    let reversed: String = first
+        .chars()
+        .rev()
+        .zip(second.chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(a, _)| a)
+        .collect();
+    reversed.chars().rev().collect()
+}
@@ -0,0 +1,279 @@
+use super::{
+    literals::{alternation_of, Literals},
+    Captures, Engine, GenericRegexParser,
+};
+use crate::automaton::aho_corasick::{AhoCorasick, MatchKind};
+use crate::automaton::alphabet::CharClasses;
+use crate::automaton::dense::DenseDFA;
+use crate::automaton::dfa::DFA;
+use crate::automaton::lazy_dfa::LazyDFA;
+use crate::automaton::nfa::NFA;
+use crate::haystack::{self, Haystack};
+use crate::thompson::{Inst, Program, Slots};
+use crate::tree::Node;
+use std::collections::HashSet;
+
+/// Most states that `Engine::Auto` determinizes for the DFA search, all
+/// automata together. Bigger patterns are searched by the lazy DFA instead.
+pub const MAX_DFA_STATES: usize = 10_000;
+
+/// What is known about a pattern before running any search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Analysis {
+    /// The only string the pattern matches, when it is a plain literal
+    pub literal: Option<String>,
+    /// Strings every match contains, used to skip ahead before searching
+    pub literals: Literals,
+    /// Branches of an alternation of plain literals, in priority order
+    pub alternation: Option<Vec<String>>,
+    /// The empty string matches, so every text does
+    pub matches_empty: bool,
+    pub has_captures: bool,
+    pub one_pass: bool,
+    /// States of the automata determinized for the DFA search: the DFA, its
+    /// reversal and both unanchored tables. A construction abandoned past its
+    /// limit records one state more than the limit
+    pub dfa_states: Option<usize>,
+}
+
+impl Analysis {
+    pub(super) fn of(tree: &Node<char>, program: &Program, one_pass: bool) -> Self {
+        let literals = Literals::of(tree);
+        // Groups would need captures, which a literal search cannot give
+        let alternation =
+            alternation_of(tree).filter(|branches| branches.len() > 1 && program.groups() == 1);
+        Analysis {
+            alternation,
+            literal: literals.exact().map(str::to_string),
+            literals,
+            matches_empty: nullable(tree),
+            has_captures: program.groups() > 1,
+            one_pass,
+            dfa_states: None,
+        }
+    }
+}
+
+impl GenericRegexParser<char> {
+    // Builds the automata the engine needs on top of the program
+    pub(super) fn build(&mut self, tree: &Node<char>) {
+        match self.engine {
+            Engine::Auto => {
+                if let Some(branches) = &self.analysis.alternation {
+                    let branches: Vec<&str> = branches.iter().map(String::as_str).collect();
+                    self.keywords =
+                        Some(AhoCorasick::with_dfa(&branches, MatchKind::LeftmostFirst));
+                }
+                if !self.build_dfa(tree, MAX_DFA_STATES) {
+                    self.lazy = Some(LazyDFA::new(&self.efa, CharClasses::from_ast(tree)));
+                }
+            }
+            Engine::Dfa => {
+                self.build_dfa(tree, usize::MAX);
+            }
+            // The DFA is never built, so memory stays linear in the pattern size
+            Engine::Nfa => self.nfa = Some(NFA::from_efa(&self.efa).unwrap()),
+            Engine::LazyDfa => {
+                self.lazy = Some(LazyDFA::new(&self.efa, CharClasses::from_ast(tree)))
+            }
+            Engine::OnePass => assert!(self.onepass.is_some(), "Pattern is not one-pass"),
+            Engine::Efa | Engine::PikeVm | Engine::Backtrack => {}
+        }
+    }
+
+    // False when the automata determinized for the search have more than
+    // `limit` states between them
+    fn build_dfa(&mut self, tree: &Node<char>, limit: usize) -> bool {
+        let built = self.try_build_dfa(tree, limit);
+        self.analysis.dfa_states = Some(built.unwrap_or(limit.saturating_add(1)));
+        return built.is_some();
+    }
+
+    // States built, each construction only getting what the previous ones left
+    fn try_build_dfa(&mut self, tree: &Node<char>, limit: usize) -> Option<usize> {
+        let dfa = DFA::from_efa_bounded(&self.efa, limit)?;
+        let mut states = dfa.automaton.len();
+        let classes = CharClasses::from_ast(tree).merge_equivalent(&dfa);
+        let minimal = DFA::minimize_with_classes(dfa, &classes).unwrap();
+        // Matches of the reversed pattern, read from the end of the text
+        let reversed = DFA::from_efa_bounded(&minimal.reverse(), limit - states)?;
+        states += reversed.automaton.len();
+        let reversed = DFA::minimize_with_classes(reversed, &classes).unwrap();
+        let reverse = DenseDFA::new(&reversed, classes.clone()).unanchored(limit - states)?;
+        states += reverse.state_count();
+        let anchored = DenseDFA::new(&minimal, classes);
+        let forward = anchored.unanchored_leftmost(limit - states)?;
+        states += forward.state_count();
+
+        self.reverse = Some(reverse);
+        self.forward = Some(forward);
+        self.dense = Some(anchored);
+        self.automaton = Some(minimal);
+        return Some(states);
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        if self.engine != Engine::Auto {
+            return self.parse(text).is_some();
+        }
+        if let Some(literal) = &self.analysis.literal {
+            return text.contains(literal.as_str());
+        }
+        if let Some(keywords) = &self.keywords {
+            return keywords.is_match(text);
+        }
+        if !text.contains(self.analysis.literals.inner.as_str()) {
+            return false;
+        }
+        // Stopping at the earliest end is enough, no start is needed
+        if let Some(forward) = &self.forward {
+            return forward.earliest_end(text).is_some();
+        }
+        return self.lazy.as_ref().unwrap().parse(text).is_some();
+    }
+
+    /// Position, in characters, of the leftmost match start.
+    pub fn parse(&self, text: &str) -> Option<usize> {
+        match self.engine {
+            Engine::Auto => {
+                if let Some(literal) = &self.analysis.literal {
+                    let offset = text.find(literal.as_str())?;
+                    return Some(text[..offset].chars().count());
+                }
+                if let Some(keywords) = &self.keywords {
+                    return keywords.find(text).map(|found| found.start);
+                }
+                // No match starts before the first occurrence of the prefix
+                let skipped = text.find(self.analysis.literals.prefix.as_str())?;
+                if !text[skipped..].contains(self.analysis.literals.inner.as_str()) {
+                    return None;
+                }
+                let rest = &text[skipped..];
+                let skipped = text[..skipped].chars().count();
+                if self.forward.is_none() {
+                    return Some(skipped + self.lazy.as_ref().unwrap().parse(rest)?);
+                }
+                let start = self.find_start(rest)?;
+                Some(skipped + rest[..start].chars().count())
+            }
+            Engine::Dfa => {
+                let start = self.find_start(text)?;
+                Some(text[..start].chars().count())
+            }
+            Engine::Nfa => self.nfa.as_ref().unwrap().parse(text),
+            Engine::LazyDfa => self.lazy.as_ref().unwrap().parse(text),
+            Engine::Efa => self.efa.parse(text),
+            Engine::PikeVm | Engine::Backtrack | Engine::OnePass => {
+                self.captures(text).map(|groups| groups[0].unwrap().0)
+            }
+        }
+    }
+
+    /// Bounds, in characters, of the leftmost-first match.
+    pub fn find(&self, text: &str) -> Option<(usize, usize)> {
+        if let (Engine::Auto, Some(literal)) = (self.engine, &self.analysis.literal) {
+            let start = self.parse(text)?;
+            return Some((start, start + literal.chars().count()));
+        }
+        if let (Engine::Auto, Some(keywords)) = (self.engine, &self.keywords) {
+            return keywords.find(text).map(|found| (found.start, found.end));
+        }
+        return self.captures(text).map(|groups| groups[0].unwrap());
+    }
+
+    /// Captures of the leftmost-first match: its start is the one `parse`
+    /// reports, and among the paths starting there the first one in priority
+    /// order wins.
+    pub fn captures(&self, text: &str) -> Option<Captures> {
+        if self.keywords.is_some() && self.engine == Engine::Auto {
+            return Some(vec![Some(self.find(text)?)]);
+        }
+        let slots = match self.engine {
+            Engine::PikeVm => self.pikevm.captures(text)?,
+            Engine::Backtrack => self.backtrack.captures(text)?,
+            Engine::OnePass => self.onepass.as_ref().unwrap().captures(text)?,
+            // The other engines cannot capture, but they know where to start
+            _ => self.captures_at(text, self.parse(text)?)?,
+        };
+        let groups = slots
+            .chunks(2)
+            .map(|bounds| Some((bounds[0]?, bounds[1]?)))
+            .collect();
+        return Some(groups);
+    }
+
+    /// Byte offset of the leftmost match start in `haystack`, which does not
+    /// have to be valid UTF-8.
+    pub fn parse_bytes(&self, haystack: &[u8]) -> Option<usize> {
+        if self.forward.is_some() && matches!(self.engine, Engine::Auto | Engine::Dfa) {
+            return self.find_start(haystack);
+        }
+        // The other engines only read text
+        let (text, offsets) = haystack::decode(haystack, self.unused_char());
+        return self.parse(&text).map(|pos| offsets[pos]);
+    }
+
+    // Character that no instruction consumes, so that it stands for invalid
+    // bytes as the DFA reads them: something no pattern character matches
+    fn unused_char(&self) -> char {
+        let used: HashSet<char> = self
+            .program
+            .insts
+            .iter()
+            .filter_map(|inst| match inst {
+                Inst::Char(token, _) => Some(*token),
+                _ => None,
+            })
+            .collect();
+        return (char::REPLACEMENT_CHARACTER..=char::MAX)
+            .find(|token| !used.contains(token))
+            .unwrap();
+    }
+
+    // The forward pass rules out haystacks without a match, and the reverse
+    // pass only reads back from where the leftmost match may end
+    fn find_start<H: Haystack + ?Sized>(&self, haystack: &H) -> Option<usize> {
+        let end = self.forward.as_ref().unwrap().leftmost_end(haystack)?;
+        return self.reverse.as_ref().unwrap().rfind_start(haystack, end);
+    }
+
+    // Captures of the match known to start at character `start`. One-pass
+    // patterns only need a single thread; otherwise short texts go to the
+    // backtracker and longer ones to the Pike VM.
+    fn captures_at(&self, text: &str, start: usize) -> Option<Slots> {
+        if let Some(onepass) = &self.onepass {
+            return onepass.captures_at(text, start);
+        }
+        let offset = text
+            .char_indices()
+            .nth(start)
+            .map_or(text.len(), |(offset, _)| offset);
+        let rest = &text[offset..];
+        // No match starts earlier, so the leftmost one in `rest` is at 0
+        let slots = if rest.chars().count() <= self.backtrack.max_haystack_len() {
+            self.backtrack.captures(rest)?
+        } else {
+            self.pikevm.captures(rest)?
+        };
+        return Some(
+            slots
+                .into_iter()
+                .map(|slot| slot.map(|pos| pos + start))
+                .collect(),
+        );
+    }
+}
+
+fn nullable(node: &Node<char>) -> bool {
+    if node.is_leaf() || node.is_epsilon() {
+        return node.is_epsilon();
+    }
+    let left = node.left.as_deref().is_some_and(nullable);
+    let right = node.right.as_deref().is_some_and(nullable);
+    match node.value {
+        '*' => true,
+        '|' => left || right,
+        '·' => left && right,
+        _ => left,
+    }
+}
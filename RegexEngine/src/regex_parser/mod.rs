@@ -1,129 +1,211 @@
+use crate::automaton::aho_corasick::AhoCorasick;
+use crate::automaton::backtrack::Backtracker;
+use crate::automaton::dense::DenseDFA;
 use crate::automaton::dfa::DFA;
 use crate::automaton::efa::EFA;
-use crate::state_generator::StateGenerator;
+use crate::automaton::lazy_dfa::LazyDFA;
+use crate::automaton::nfa::NFA;
+use crate::automaton::onepass::OnePass;
+use crate::automaton::pikevm::PikeVM;
+use crate::thompson::Program;
 use crate::tree::Node;
-use crate::type_defs::State;
 use std::collections::VecDeque;
+use std::fmt;
+
+mod compare;
+mod fixtures;
+mod literals;
+mod meta;
+mod printer;
+mod set;
+
+pub use compare::Counterexample;
+pub use literals::Literals;
+pub use meta::{Analysis, MAX_DFA_STATES};
+pub(crate) use set::compile_union;
+pub use set::RegexSet;
 
 pub type RegexParser = GenericRegexParser<char>;
 
+/// Engine answering the queries of a parser
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Engine {
+    /// Lets the meta layer pick the cheapest correct engine for every query
+    Auto,
+    /// Minimized DFA. Fastest to run, but can have exponentially many states
+    Dfa,
+    /// Epsilon-free NFA simulated one set of states at a time
+    Nfa,
+    /// DFA determinized during the search, within a bounded state cache
+    LazyDfa,
+    /// Backtracking over the epsilon automaton. Exponential in the worst case
+    Efa,
+    /// Thompson program simulated one thread per instruction
+    PikeVm,
+    /// Thompson program explored depth first, at most once per position
+    Backtrack,
+    /// One-pass DFA. Only valid for one-pass patterns
+    OnePass,
+}
+
 pub struct GenericRegexParser<T> {
-    automaton: DFA<T>,
+    engine: Engine,
+    analysis: Analysis,
+    automaton: Option<DFA<T>>,
+    dense: Option<DenseDFA>,
+    forward: Option<DenseDFA>,
+    reverse: Option<DenseDFA>,
+    nfa: Option<NFA<T>>,
+    lazy: Option<LazyDFA>,
     efa: EFA<T>,
+    program: Program,
+    pikevm: PikeVM,
+    backtrack: Backtracker,
+    onepass: Option<OnePass>,
+    keywords: Option<AhoCorasick>,
 }
 
+/// Bounds, in characters, of every capture group. Group 0 is the whole match
+/// and a group that did not take part in it is `None`.
+pub type Captures = Vec<Option<(usize, usize)>>;
+
 impl GenericRegexParser<char> {
+    /// Panics if `regex` is malformed; `new` reports it instead.
     pub fn from(regex: &str) -> Self {
-        let processed_regex = add_implicit_concatenation(regex);
-
-        let tree = parse_regex(&processed_regex);
-        let mut post_order: Vec<char> = Vec::new();
-        tree.post_order(&mut post_order);
-
-        let mut efa: EFA<char> = EFA::new();
-        let mut state_generator: StateGenerator<usize, (State, State)> = StateGenerator::new();
-        let mut tree_stack: VecDeque<usize> = VecDeque::new();
-
-        for (pos, token) in post_order.iter().enumerate() {
-            if !is_operator(token) {
-                let (i_state, f_state) = state_generator.generate_for(&pos);
-
-                efa.transition(i_state, Some(*token), f_state);
-                efa.empty_transition(f_state);
-
-                tree_stack.push_back(pos);
-            } else {
-                match token {
-                    '*' => {
-                        let (i_state, f_state) = state_generator.generate_for(&pos);
-                        // child position
-                        let child = tree_stack
-                            .pop_back()
-                            .expect("Operator \'*\' expected an operand");
-                        let (child_i, child_f) = state_generator.get_states(&child).unwrap();
-
-                        efa.transition(i_state, None, f_state);
-                        efa.transition(i_state, None, child_i);
-
-                        efa.transition(child_f, None, f_state);
-                        efa.transition(child_f, None, child_i);
-
-                        tree_stack.push_back(pos);
-                    }
-                    '|' => {
-                        let (i_state, f_state) = state_generator.generate_for(&pos);
-                        let child_r = tree_stack
-                            .pop_back()
-                            .expect("Operator \'·\' expected two operands");
-                        let child_l = tree_stack
-                            .pop_back()
-                            .expect("Operator \'·\' expected two operands");
-
-                        let (child_l_i, child_l_f) = state_generator.get_states(&child_l).unwrap();
-                        let (child_r_i, child_r_f) = state_generator.get_states(&child_r).unwrap();
-
-                        efa.transition(i_state, None, child_l_i);
-                        efa.transition(i_state, None, child_r_i);
-
-                        efa.transition(child_l_f, None, f_state);
-                        efa.transition(child_r_f, None, f_state);
-
-                        efa.empty_transition(f_state);
-
-                        tree_stack.push_back(pos);
-                    }
-                    '·' => {
-                        let child_r = tree_stack
-                            .pop_back()
-                            .expect("Operator \'·\' expected two operands");
-                        let child_l = tree_stack
-                            .pop_back()
-                            .expect("Operator \'·\' expected two operands");
-
-                        let (child_l_i, child_l_f) = state_generator.get_states(&child_l).unwrap();
-                        let (child_r_i, child_r_f) = state_generator.get_states(&child_r).unwrap();
-
-                        state_generator.insert_with(&pos, &(child_l_i, child_r_f));
-
-                        efa.transition(child_l_f, None, child_r_i);
-
-                        tree_stack.push_back(pos);
-                    }
-                    _ => panic!("Unknown token {}", token),
-                }
-            }
+        Self::with_engine(regex, Engine::Auto)
+    }
+    pub fn new(regex: &str) -> Result<Self, ParseError> {
+        Self::try_with_engine(regex, Engine::Auto)
+    }
+    /// Compiles `regex` for `engine`. Anything but `Engine::Auto` forces every
+    /// query through that engine, which is mostly useful for testing.
+    pub fn with_engine(regex: &str, engine: Engine) -> Self {
+        match Self::try_with_engine(regex, engine) {
+            Ok(parser) => parser,
+            Err(error) => panic!("Invalid pattern {:?}: {}", regex, error),
         }
-
-        let root_pos = tree_stack
-            .pop_back()
-            .expect("Root of the expression was not pushed to the stack");
-        let (start, end) = state_generator.get_states(&root_pos).unwrap();
-        efa.set_start(start);
-        efa.set_end(end);
-
-        let dfa = DFA::from_efa(&efa.clone()).unwrap();
-        // let automaton = dfa;
-        let automaton = DFA::minimize_from(dfa).unwrap();
-        // automaton.print();
-        return GenericRegexParser { automaton, efa };
     }
-    pub fn parse(&self, text: &str) -> Option<usize> {
-        self.automaton.parse(text)
+    pub fn try_with_engine(regex: &str, engine: Engine) -> Result<Self, ParseError> {
+        let tree = parse_ast(regex)?;
+        let program = Program::compile(&tree);
+        let efa = program.to_efa();
+        let onepass = OnePass::new(&program);
+
+        let mut parser = GenericRegexParser {
+            engine,
+            analysis: Analysis::of(&tree, &program, onepass.is_some()),
+            automaton: None,
+            dense: None,
+            forward: None,
+            reverse: None,
+            nfa: None,
+            lazy: None,
+            efa,
+            pikevm: PikeVM::new(&program),
+            backtrack: Backtracker::new(&program),
+            onepass,
+            keywords: None,
+            program,
+        };
+        parser.build(&tree);
+        return Ok(parser);
+    }
+    pub fn get_engine(&self) -> Engine {
+        self.engine
+    }
+    pub fn get_analysis(&self) -> &Analysis {
+        &self.analysis
     }
     pub fn get_efa_temp(self) -> EFA<char> {
         return self.efa;
     }
-    pub fn get_dfa_temp(&self) -> DFA<char> {
+    pub fn get_dfa_temp(&self) -> Option<DFA<char>> {
         return self.automaton.clone();
     }
+    pub fn get_dense(&self) -> Option<&DenseDFA> {
+        self.dense.as_ref()
+    }
+    pub fn get_forward(&self) -> Option<&DenseDFA> {
+        self.forward.as_ref()
+    }
+    pub fn get_reverse(&self) -> Option<&DenseDFA> {
+        self.reverse.as_ref()
+    }
+    pub fn get_nfa_temp(&self) -> Option<NFA<char>> {
+        return self.nfa.clone();
+    }
+    pub fn get_program(&self) -> &Program {
+        &self.program
+    }
+    pub fn get_pikevm(&self) -> &PikeVM {
+        &self.pikevm
+    }
+    pub fn get_backtracker(&self) -> &Backtracker {
+        &self.backtrack
+    }
+    pub fn get_onepass(&self) -> Option<&OnePass> {
+        self.onepass.as_ref()
+    }
+    pub fn get_keywords(&self) -> Option<&AhoCorasick> {
+        self.keywords.as_ref()
+    }
+}
+
+/// Parses a pattern into its syntax tree without compiling it. An empty
+/// `(?:)` group stands for the empty string and becomes `Node::epsilon`.
+pub fn parse_ast(regex: &str) -> Result<Node<char>, ParseError> {
+    let tokens = add_implicit_concatenation(&tokenize(regex, false)?);
+    parse_regex(&tokens)
+}
+
+/// Like `parse_ast`, but `&` intersects two patterns and a prefix `~`
+/// complements one. Only `derivative::Term` can compile those.
+pub fn parse_boolean_ast(regex: &str) -> Result<Node<char>, ParseError> {
+    let tokens = add_implicit_concatenation(&tokenize(regex, true)?);
+    parse_regex(&tokens)
+}
+
+/// Reason why a pattern could not be parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    /// The pattern ends with a `\` that escapes nothing
+    DanglingEscape,
+    /// A `)` without its `(`, or a `(` that is never closed
+    UnbalancedParenthesis,
+    /// An operator, or a group, with nothing to apply to, e.g. `a()`, `|a` or `*`
+    MissingOperand,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let message = match self {
+            ParseError::DanglingEscape => "pattern ends with a dangling escape",
+            ParseError::UnbalancedParenthesis => "unbalanced parenthesis",
+            ParseError::MissingOperand => "operator or group without an operand",
+        };
+        write!(f, "{}", message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+// Marks a `(?:` group on the operator stack. It is never pushed as a literal
+const NON_CAPTURING: char = ':';
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Literal(char),
+    Operator(char),
 }
 
 fn hierarchy(ch: char) -> u8 {
     match ch {
-        '(' | ')' => 1,
+        '(' | ')' | NON_CAPTURING => 1,
         '|' => 2,
-        '·' => 3,
-        '*' => 4,
+        '&' => 3,
+        '·' => 4,
+        '~' => 5,
+        '*' => 6,
         _ => 0,
     }
 }
@@ -136,90 +218,153 @@ fn is_operator(ch: &char) -> bool {
     return false;
 }
 
-fn is_alphabet(ch: &char) -> bool {
-    return !is_operator(ch);
+// Only operators in the syntax of `parse_boolean_ast`, plain characters otherwise
+fn is_boolean_operator(ch: &char) -> bool {
+    return *ch == '&' || *ch == '~';
+}
+
+// Escaped whenever printed, so that either syntax reads the character back
+fn is_escapable(ch: &char) -> bool {
+    return is_operator(ch) || is_boolean_operator(ch) || *ch == '\\' || *ch == '?';
 }
 
-fn parse_regex(r: &str) -> Node<char> {
+fn tokenize(regex: &str, boolean: bool) -> Result<Vec<Token>, ParseError> {
+    let mut tokens: Vec<Token> = Vec::new();
+    let chars: Vec<char> = regex.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+        let ch = chars[i];
+        if ch == '\\' {
+            // An escaped character is always a literal, even if it is an operator
+            i += 1;
+            let escaped = *chars.get(i).ok_or(ParseError::DanglingEscape)?;
+            tokens.push(Token::Literal(escaped));
+        } else if ch == '(' && chars[i + 1..].starts_with(&['?', ':']) {
+            i += 2;
+            tokens.push(Token::Operator(NON_CAPTURING));
+        } else if is_operator(&ch) || (boolean && is_boolean_operator(&ch)) {
+            tokens.push(Token::Operator(ch));
+        } else {
+            tokens.push(Token::Literal(ch));
+        }
+        i += 1;
+    }
+    Ok(tokens)
+}
+
+fn parse_regex(tokens: &[Token]) -> Result<Node<char>, ParseError> {
+    // Trees below these heights belong outside the open groups, so the
+    // operators of a group can never take them as operands
+    let mut floors: Vec<usize> = Vec::new();
     let mut op_stack: VecDeque<char> = VecDeque::new(); // operator stack
     let mut tr_stack: VecDeque<Node<char>> = VecDeque::new(); // tree stack
-    for ch in r.chars() {
-        match ch {
-            '(' => op_stack.push_back(ch),
-            ')' => loop {
-                build_tree(&mut op_stack, &mut tr_stack);
-                let op = op_stack.back();
-                if op.is_none() || *op.unwrap() == '(' {
-                    op_stack.pop_back();
-                    break;
+    for token in tokens {
+        let floor = floors.last().copied().unwrap_or(0);
+        match *token {
+            Token::Operator(ch @ ('(' | NON_CAPTURING)) => {
+                floors.push(tr_stack.len());
+                op_stack.push_back(ch);
+            }
+            // A prefix operator has no operand yet, so nothing is reduced before it
+            Token::Operator('~') => op_stack.push_back('~'),
+            Token::Operator(')') => {
+                while op_stack
+                    .back()
+                    .is_some_and(|op| *op != '(' && *op != NON_CAPTURING)
+                {
+                    build_tree(&mut op_stack, &mut tr_stack, floor)?;
                 }
-            },
-            _ => {
-                if is_operator(&ch) {
-                    while hierarchy(*op_stack.back().unwrap_or(&' ')) >= hierarchy(ch) {
-                        build_tree(&mut op_stack, &mut tr_stack);
+                let open = op_stack
+                    .pop_back()
+                    .ok_or(ParseError::UnbalancedParenthesis)?;
+                floors.pop();
+                if tr_stack.len() <= floor {
+                    // Only an empty non-capturing group is the empty string
+                    if open != NON_CAPTURING {
+                        return Err(ParseError::MissingOperand);
                     }
-                    op_stack.push_back(ch);
-                } else {
-                    tr_stack.push_back(Node {
-                        value: ch,
-                        left: None,
-                        right: None,
-                    });
+                    tr_stack.push_back(Node::epsilon());
+                }
+                // Capturing groups stay in the tree so that they can be numbered
+                if open == '(' {
+                    let inner = tr_stack.pop_back().unwrap();
+                    tr_stack.push_back(Node::with_l('(', inner));
+                }
+            }
+            Token::Operator(ch) => {
+                while hierarchy(*op_stack.back().unwrap_or(&' ')) >= hierarchy(ch) {
+                    build_tree(&mut op_stack, &mut tr_stack, floor)?;
                 }
+                op_stack.push_back(ch);
             }
+            Token::Literal(ch) => tr_stack.push_back(Node::leaf(ch)),
         }
     }
 
     while !op_stack.is_empty() {
-        build_tree(&mut op_stack, &mut tr_stack);
+        build_tree(&mut op_stack, &mut tr_stack, 0)?;
     }
-    return tr_stack.back().unwrap().clone();
+    if tr_stack.len() != 1 {
+        return Err(ParseError::MissingOperand);
+    }
+    return Ok(tr_stack.pop_back().unwrap());
 }
 
-fn build_tree(op_stack: &mut VecDeque<char>, tr_stack: &mut VecDeque<Node<char>>) {
+// Reduces the operator on top of the stack, which may only take the trees
+// above `floor` as operands
+fn build_tree(
+    op_stack: &mut VecDeque<char>,
+    tr_stack: &mut VecDeque<Node<char>>,
+    floor: usize,
+) -> Result<(), ParseError> {
     let op = op_stack.pop_back().unwrap();
-    let t1 = tr_stack.pop_back().unwrap();
+    let arity = match op {
+        '|' | '&' | '·' => 2,
+        '*' | '~' => 1,
+        // Only left on the stack by a group that is never closed
+        '(' | NON_CAPTURING => return Err(ParseError::UnbalancedParenthesis),
+        _ => unreachable!("Unknown operator {}", op),
+    };
+    if tr_stack.len() < floor + arity {
+        return Err(ParseError::MissingOperand);
+    }
 
-    match op {
-        '|' | '·' => {
+    let t1 = tr_stack.pop_back().unwrap();
+    let t = match arity {
+        2 => {
             let t2 = tr_stack.pop_back().unwrap();
-            let t: Node<char> = Node::with_lr(op, t2, t1);
-            tr_stack.push_back(t);
-        }
-        '*' => {
-            let t: Node<char> = Node::with_l(op, t1);
-            tr_stack.push_back(t);
+            Node::with_lr(op, t2, t1)
         }
-
-        _ => {
-            println!("Unimplemented operand {}", op);
-            tr_stack.push_back(t1);
-        }
-    }
+        _ => Node::with_l(op, t1),
+    };
+    tr_stack.push_back(t);
+    return Ok(());
 }
 
-fn add_implicit_concatenation(regex: &str) -> String {
-    let mut result = String::new();
-    let chars: Vec<char> = regex.chars().collect();
+fn add_implicit_concatenation(tokens: &[Token]) -> Vec<Token> {
+    let mut result: Vec<Token> = Vec::new();
 
-    for i in 0..chars.len() {
-        let current = chars[i];
+    for i in 0..tokens.len() {
+        let current = tokens[i];
         result.push(current);
 
         // Check if we need to add implicit concatenation
-        if i < chars.len() - 1 {
-            let next = chars[i + 1];
-
-            let should_add_concat = match (current, next) {
-                (c1, c2) if is_alphabet(&c1) && is_alphabet(&c2) => true,
-                (')', '(') => true,
-                ('*', c) if is_alphabet(&c) => true,
-                ('*', '(') => true,
-                _ => false,
-            };
-            if should_add_concat {
-                result.push('·');
+        if i < tokens.len() - 1 {
+            let next = tokens[i + 1];
+
+            let ends_operand = matches!(
+                current,
+                Token::Literal(_) | Token::Operator(')') | Token::Operator('*')
+            );
+            let starts_operand = matches!(
+                next,
+                Token::Literal(_)
+                    | Token::Operator('(')
+                    | Token::Operator(NON_CAPTURING)
+                    | Token::Operator('~')
+            );
+            if ends_operand && starts_operand {
+                result.push(Token::Operator('·'));
             }
         }
     }
@@ -0,0 +1,99 @@
+use super::{hierarchy, is_escapable};
+use crate::tree::Node;
+use std::fmt;
+
+// Binding strength of a node. Leaves bind tighter than any operator
+const ATOM: u8 = 7;
+
+impl Node<char> {
+    /// Renders the tree back into regex syntax, escaping literal operators and
+    /// only adding the parentheses the parser needs to rebuild the same tree.
+    pub fn to_regex(&self) -> String {
+        let mut result = String::new();
+        write_node(self, &mut result);
+        result
+    }
+}
+
+impl fmt::Display for Node<char> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_regex())
+    }
+}
+
+fn binding(node: &Node<char>) -> u8 {
+    if node.is_leaf() || node.is_epsilon() || node.value == '(' {
+        return ATOM;
+    }
+    return hierarchy(node.value);
+}
+
+fn write_node(node: &Node<char>, result: &mut String) {
+    if node.is_epsilon() {
+        result.push_str("(?:)");
+        return;
+    }
+    if node.is_leaf() {
+        if is_escapable(&node.value) {
+            result.push('\\');
+        }
+        result.push(node.value);
+        return;
+    }
+
+    let own = hierarchy(node.value);
+    match node.value {
+        '(' => {
+            let child = node.left.as_ref().expect("Group expected an operand");
+            result.push('(');
+            write_node(child, result);
+            result.push(')');
+        }
+        '*' => {
+            let child = node
+                .left
+                .as_ref()
+                .expect("Operator '*' expected an operand");
+            // `a**` parses back into nested stars, so only binary operators need grouping
+            write_operand(child, binding(child) < own, result);
+            result.push('*');
+        }
+        '~' => {
+            let child = node
+                .left
+                .as_ref()
+                .expect("Operator '~' expected an operand");
+            result.push('~');
+            write_operand(child, binding(child) < own, result);
+        }
+        '|' | '&' | '·' => {
+            let left = node
+                .left
+                .as_ref()
+                .expect("Binary operator expected two operands");
+            let right = node
+                .right
+                .as_ref()
+                .expect("Binary operator expected two operands");
+            // Binary operators are left associative, so a right operand of the same kind
+            // has to keep its parentheses
+            write_operand(left, binding(left) < own, result);
+            if node.value != '·' {
+                result.push(node.value);
+            }
+            write_operand(right, binding(right) <= own, result);
+        }
+        _ => panic!("Unknown operator {}", node.value),
+    }
+}
+
+// Parentheses added for precedence must not introduce a new capture group
+fn write_operand(node: &Node<char>, grouped: bool, result: &mut String) {
+    if grouped {
+        result.push_str("(?:");
+    }
+    write_node(node, result);
+    if grouped {
+        result.push(')');
+    }
+}
@@ -0,0 +1,191 @@
+use super::{literals::Literals, parse_ast, ParseError, MAX_DFA_STATES};
+use crate::automaton::aho_corasick::{AhoCorasick, MatchKind};
+use crate::automaton::alphabet::CharClasses;
+use crate::automaton::dense::{DenseDFA, DEAD};
+use crate::automaton::dfa::DFA;
+use crate::automaton::efa::EFA;
+use crate::thompson::Program;
+use crate::tree::Node;
+use crate::type_defs::PatternId;
+
+/// Several patterns searched together in a single pass.
+///
+/// The patterns are united into one EFA whose end states remember their
+/// pattern, so every final state of the resulting DFA carries the ids of the
+/// patterns matching there. Sets of plain literals use Aho-Corasick instead.
+pub struct RegexSet {
+    patterns: Vec<String>,
+    matcher: Matcher,
+}
+
+enum Matcher {
+    Dfa {
+        automaton: DFA<char>,
+        // Boxed since the class tables alone dwarf the other variant
+        anchored: Box<DenseDFA>,
+        // None when the unanchored table outgrows MAX_DFA_STATES, in which
+        // case the anchored one is run from every start
+        forward: Option<Box<DenseDFA>>,
+    },
+    Keywords(AhoCorasick),
+}
+
+impl RegexSet {
+    /// Panics if a pattern is malformed; `new` reports it instead.
+    pub fn from(patterns: &[&str]) -> Self {
+        match Self::new(patterns) {
+            Ok(set) => set,
+            Err(error) => panic!("Invalid pattern set {:?}: {}", patterns, error),
+        }
+    }
+
+    pub fn new(patterns: &[&str]) -> Result<Self, ParseError> {
+        let trees: Vec<Node<char>> = patterns
+            .iter()
+            .map(|pattern| parse_ast(pattern))
+            .collect::<Result<_, _>>()?;
+        let names = patterns.iter().map(|pattern| pattern.to_string()).collect();
+
+        let keywords: Option<Vec<String>> = trees
+            .iter()
+            .map(|tree| Literals::of(tree).exact().map(str::to_string))
+            .collect();
+        if let Some(keywords) = keywords.filter(|keywords| !keywords.is_empty()) {
+            let keywords: Vec<&str> = keywords.iter().map(String::as_str).collect();
+            return Ok(RegexSet {
+                patterns: names,
+                matcher: Matcher::Keywords(AhoCorasick::with_dfa(
+                    &keywords,
+                    MatchKind::LeftmostFirst,
+                )),
+            });
+        }
+
+        let (minimal, classes) = compile_union(&trees);
+        let anchored = DenseDFA::new(&minimal, classes);
+        let forward = anchored.unanchored(MAX_DFA_STATES).map(Box::new);
+
+        Ok(RegexSet {
+            patterns: names,
+            matcher: Matcher::Dfa {
+                automaton: minimal,
+                anchored: Box::new(anchored),
+                forward,
+            },
+        })
+    }
+
+    /// Ids of the patterns matching somewhere in `text`, in increasing order.
+    pub fn matches(&self, text: &str) -> Vec<PatternId> {
+        let (anchored, forward) = match &self.matcher {
+            Matcher::Dfa {
+                anchored, forward, ..
+            } => (anchored, forward),
+            Matcher::Keywords(keywords) => {
+                let mut matched: Vec<PatternId> = keywords
+                    .find_overlapping(text)
+                    .into_iter()
+                    .map(|found| found.pattern)
+                    .collect();
+                matched.sort();
+                matched.dedup();
+                return matched;
+            }
+        };
+        let mut matched = vec![false; self.patterns.len()];
+        let mut count = 0;
+        match forward {
+            Some(forward) => {
+                mark_matches(forward, text, &mut matched, &mut count);
+            }
+            None => {
+                let starts = text.char_indices().map(|(offset, _)| offset);
+                for offset in starts.chain([text.len()]) {
+                    if mark_matches(anchored, &text[offset..], &mut matched, &mut count) {
+                        break;
+                    }
+                }
+            }
+        }
+        return (0..self.patterns.len())
+            .filter(|pattern| matched[*pattern])
+            .collect();
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        match &self.matcher {
+            Matcher::Dfa {
+                forward: Some(forward),
+                ..
+            } => forward.earliest_end(text).is_some(),
+            Matcher::Dfa { anchored, .. } => anchored.parse(text).is_some(),
+            Matcher::Keywords(keywords) => keywords.is_match(text),
+        }
+    }
+
+    pub fn patterns(&self) -> &[String] {
+        &self.patterns
+    }
+
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// None for a set of literals, which is searched with Aho-Corasick.
+    pub fn get_dfa(&self) -> Option<&DFA<char>> {
+        match &self.matcher {
+            Matcher::Dfa { automaton, .. } => Some(automaton),
+            Matcher::Keywords(_) => None,
+        }
+    }
+}
+
+// Marks the patterns of `dfa` matching a prefix of `text`, counting the new
+// ones. True once every pattern is marked, since reading on cannot add anything
+fn mark_matches(dfa: &DenseDFA, text: &str, matched: &mut [bool], count: &mut usize) -> bool {
+    let mut state = dfa.start;
+    let mut tokens = text.chars();
+    loop {
+        for pattern in dfa.patterns_of(state) {
+            if !matched[*pattern] {
+                matched[*pattern] = true;
+                *count += 1;
+            }
+        }
+        if *count == matched.len() {
+            return true;
+        }
+        match tokens.next() {
+            Some(token) => state = dfa.next_state(state, token),
+            None => return false,
+        }
+        if state == DEAD {
+            return false;
+        }
+    }
+}
+
+/// Minimal DFA of the union of `trees`, whose final states carry the index of
+/// every tree they accept, with the character classes it was minimized over.
+pub(crate) fn compile_union(trees: &[Node<char>]) -> (DFA<char>, CharClasses) {
+    let parts: Vec<EFA<char>> = trees
+        .iter()
+        .map(|tree| Program::compile(tree).to_efa())
+        .collect();
+    let efa = EFA::union(&parts);
+
+    let dfa = DFA::from_efa(&efa).unwrap();
+    let alphabet: Vec<char> = dfa
+        .automaton
+        .values()
+        .flat_map(|transitions| transitions.keys())
+        .cloned()
+        .collect();
+    let classes = CharClasses::from_alphabet(&alphabet).merge_equivalent(&dfa);
+    let minimal = DFA::minimize_with_classes(dfa, &classes).unwrap();
+    (minimal, classes)
+}
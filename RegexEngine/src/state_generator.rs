@@ -70,13 +70,23 @@ impl<
     }
 
     pub fn get_states(&self, value: &ValueType) -> Option<ValueState> {
-        Some(self.states.get(&value)?.clone())
+        Some(self.states.get(value)?.clone())
     }
 
     pub fn get_value(&self, state: &ValueState) -> Option<ValueType> {
-        Some(self.values.get(&state)?.clone())
+        Some(self.values.get(state)?.clone())
     }
     fn generate_return(&mut self) -> ValueState {
         ValueState::generate(&mut self.state_counter)
     }
 }
+
+impl<
+        ValueType: Eq + Hash + Clone + Debug,
+        ValueState: StateGeneration + Clone + Eq + Hash + Debug,
+    > Default for StateGenerator<ValueType, ValueState>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,90 @@
+use super::RegexParser;
+use crate::automaton::aho_corasick::{AhoCorasick, Match, MatchKind};
+use crate::regex_parser::Engine;
+use crate::RegexSet;
+
+// =============================================================================
+// AHO-CORASICK TESTS
+// =============================================================================
+
+fn spans(matches: &[Match]) -> Vec<(usize, usize, usize)> {
+    matches
+        .iter()
+        .map(|m| (m.pattern, m.start, m.end))
+        .collect()
+}
+
+#[test]
+fn overlapping_search_reports_every_occurrence() {
+    let keywords = ["he", "she", "his", "hers"];
+    for automaton in [
+        AhoCorasick::new(&keywords, MatchKind::LeftmostFirst),
+        AhoCorasick::with_dfa(&keywords, MatchKind::LeftmostFirst),
+    ] {
+        let found = automaton.find_overlapping("ushers");
+        assert_eq!(spans(&found), vec![(0, 2, 4), (1, 1, 4), (3, 2, 6)]);
+    }
+}
+
+#[test]
+fn leftmost_first_prefers_earlier_patterns() {
+    let automaton = AhoCorasick::new(&["ab", "abcd", "bcde"], MatchKind::LeftmostFirst);
+    assert_eq!(
+        automaton.find("xabcde").map(|m| (m.pattern, m.end)),
+        Some((0, 3))
+    );
+    let automaton = AhoCorasick::new(&["abcd", "ab"], MatchKind::LeftmostFirst);
+    assert_eq!(
+        automaton.find("xabcde").map(|m| (m.pattern, m.end)),
+        Some((0, 5))
+    );
+}
+
+#[test]
+fn leftmost_longest_prefers_longer_patterns() {
+    let automaton = AhoCorasick::with_dfa(&["ab", "abcd", "bcde"], MatchKind::LeftmostLongest);
+    assert_eq!(
+        automaton.find("xabcde").map(|m| (m.pattern, m.end)),
+        Some((1, 5))
+    );
+    let found = automaton.find_iter("abcdab ab");
+    assert_eq!(spans(&found), vec![(1, 0, 4), (0, 4, 6), (0, 7, 9)]);
+    assert!(!automaton.is_match("acbd"));
+}
+
+#[test]
+fn literal_alternations_use_aho_corasick() {
+    let parser = RegexParser::from("cat|dog|catalog");
+    assert!(parser.get_keywords().is_some());
+    assert_eq!(parser.get_analysis().alternation.as_ref().unwrap().len(), 3);
+    assert!(RegexParser::from("(cat|dog)").get_keywords().is_none());
+    assert!(RegexParser::from("ca*t|dog").get_keywords().is_none());
+
+    let backtrack = RegexParser::with_engine("cat|dog|catalog", Engine::Backtrack);
+    for text in ["", "a catalog", "hotdog", "cacat", "ddog"] {
+        assert_eq!(parser.find(text), backtrack.find(text), "{:?}", text);
+        assert_eq!(parser.parse(text), backtrack.parse(text), "{:?}", text);
+    }
+}
+
+#[test]
+fn literal_sets_skip_the_dfa() {
+    let set = RegexSet::from(&["error", "warn", "err"]);
+    assert!(set.get_dfa().is_none());
+    assert_eq!(set.matches("an error"), vec![0, 2]);
+    assert_eq!(set.matches("warning"), vec![1]);
+    assert!(!set.is_match("info"));
+}
+
+#[test]
+fn keyword_dense_text_is_iterated_in_linear_time() {
+    let automaton = AhoCorasick::with_dfa(&["α", "ab"], MatchKind::LeftmostFirst);
+    let text = "αab".repeat(100_000);
+    let found = automaton.find_iter(&text);
+    assert_eq!(found.len(), 200_000);
+    assert_eq!(spans(&found[..2]), vec![(0, 0, 1), (1, 1, 3)]);
+    assert_eq!(found.last().unwrap().end, 300_000);
+    // Empty patterns match between every two characters
+    let empty = AhoCorasick::new(&[""], MatchKind::LeftmostFirst);
+    assert_eq!(empty.find_iter("ααα").len(), 4);
+}
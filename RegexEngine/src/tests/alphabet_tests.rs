@@ -0,0 +1,70 @@
+use super::RegexParser;
+use crate::automaton::alphabet::CharClasses;
+use crate::automaton::dfa::DFA;
+use crate::regex_parser::parse_ast;
+use crate::thompson::Program;
+
+// =============================================================================
+// CHARACTER EQUIVALENCE CLASS TESTS
+// =============================================================================
+
+fn dfa_for(pattern: &str) -> DFA<char> {
+    DFA::from_efa(&Program::compile(&parse_ast(pattern).unwrap()).to_efa()).unwrap()
+}
+
+#[test]
+fn literals_get_their_own_class() {
+    let classes = CharClasses::from_ast(&parse_ast("(a|b)*cé").unwrap());
+    assert_eq!(classes.len(), 5);
+    assert_ne!(classes.get('a'), classes.get('b'));
+    assert_ne!(classes.get('é'), 0);
+    assert_eq!(classes.get('z'), 0);
+    assert_eq!(classes.get('ü'), 0);
+}
+
+#[test]
+fn interchangeable_characters_are_merged() {
+    let pattern = "(a|b|ü)*c";
+    let classes =
+        CharClasses::from_ast(&parse_ast(pattern).unwrap()).merge_equivalent(&dfa_for(pattern));
+    assert_eq!(classes.len(), 3);
+    assert_eq!(classes.get('a'), classes.get('b'));
+    assert_eq!(classes.get('a'), classes.get('ü'));
+    assert_ne!(classes.get('a'), classes.get('c'));
+    assert_eq!(classes.members(classes.get('a')), &['a', 'b', 'ü']);
+}
+
+#[test]
+fn dense_table_uses_merged_classes() {
+    let parser = RegexParser::from("(a|b|c|d)*e");
+    let dense = parser.get_dense().unwrap();
+    // Class 0, one class for a-d and one for e
+    assert_eq!(dense.stride, 3);
+    assert_eq!(parser.parse("xxabcdde"), Some(2));
+    assert_eq!(parser.parse("abcd"), None);
+}
+
+#[test]
+fn class_minimization_matches_plain_minimization() {
+    for pattern in ["(a|b)*abb", "(a|b|c)*c", "ab|cd", "x(a|b*)*y"] {
+        let classes =
+            CharClasses::from_ast(&parse_ast(pattern).unwrap()).merge_equivalent(&dfa_for(pattern));
+        let with_classes = DFA::minimize_with_classes(dfa_for(pattern), &classes).unwrap();
+        let plain = DFA::minimize_from(dfa_for(pattern)).unwrap();
+        assert_eq!(
+            with_classes.automaton.len(),
+            plain.automaton.len(),
+            "{}",
+            pattern
+        );
+        for text in ["abb", "aabb", "cc", "cd", "xaby", "xy", "b"] {
+            assert_eq!(
+                with_classes.parse(text),
+                plain.parse(text),
+                "{} on {}",
+                pattern,
+                text
+            );
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use super::RegexParser;
+use crate::automaton::dfa::{CHAR_COUNT, DFA};
+
+// =============================================================================
+// LANGUAGE ANALYSIS TESTS
+// =============================================================================
+
+fn dfa_for(pattern: &str) -> DFA<char> {
+    RegexParser::from(pattern).language()
+}
+
+#[test]
+fn emptiness_and_universality() {
+    let pattern = dfa_for("(a|b)*abb");
+    assert!(!pattern.is_empty());
+    assert!(DFA::difference(&pattern, &pattern).is_empty());
+    assert!(DFA::intersect(&dfa_for("a*"), &dfa_for("b")).is_empty());
+    // Only a complement can accept characters the pattern never mentions
+    assert!(!dfa_for("(a|b)*").is_universal());
+    assert!(DFA::union(&pattern, &pattern.complement()).is_universal());
+}
+
+#[test]
+fn finiteness_follows_accepting_cycles() {
+    assert!(dfa_for("ab|cd|e").is_finite());
+    assert!(dfa_for("(a|b)(c|d)").is_finite());
+    assert!(!dfa_for("ab*").is_finite());
+    assert!(!dfa_for("(ab)*c").is_finite());
+    // The loop never reaches a final state, so it adds nothing
+    assert!(DFA::intersect(&dfa_for("a*"), &dfa_for("aa|a")).is_finite());
+}
+
+#[test]
+fn shortest_and_longest_lengths() {
+    let pattern = dfa_for("a|bcd|ef");
+    assert_eq!(pattern.min_length(), Some(1));
+    assert_eq!(pattern.max_length(), Some(3));
+    let pattern = dfa_for("(ab)*c");
+    assert_eq!(pattern.min_length(), Some(1));
+    assert_eq!(pattern.max_length(), None);
+    let nothing = DFA::intersect(&dfa_for("a"), &dfa_for("b"));
+    assert_eq!(nothing.min_length(), None);
+    assert_eq!(nothing.max_length(), None);
+    assert_eq!(dfa_for("a*").min_length(), Some(0));
+}
+
+#[test]
+fn counts_strings_of_every_length() {
+    let pattern = dfa_for("(a|b)*c");
+    assert_eq!(pattern.count_strings_of_length(0), 0);
+    assert_eq!(pattern.count_strings_of_length(1), 1);
+    assert_eq!(pattern.count_strings_of_length(4), 8);
+    let pattern = dfa_for("ab|cd|a(b|c)");
+    assert_eq!(pattern.count_strings_of_length(2), 3);
+}
+
+#[test]
+fn counts_default_transitions_and_saturates() {
+    // Everything but "a": every other single character
+    let complement = dfa_for("a").complement();
+    assert_eq!(complement.count_strings_of_length(0), 1);
+    assert_eq!(complement.count_strings_of_length(1), CHAR_COUNT - 1);
+    assert_eq!(
+        complement.count_strings_of_length(2),
+        CHAR_COUNT * CHAR_COUNT
+    );
+    assert_eq!(complement.count_strings_of_length(20), u128::MAX);
+}
@@ -0,0 +1,92 @@
+use super::RegexParser;
+use crate::automaton::backtrack::Backtracker;
+
+// =============================================================================
+// BACKTRACKER AND PIKE VM TESTS
+// =============================================================================
+
+const PATTERNS: [&str; 8] = [
+    "a",
+    "ab|cd",
+    "(a|ab)(c|bcd)",
+    "(a*)(a*)b",
+    "((a)|b)*c",
+    "(a*)*b",
+    "x(y|yz)*z",
+    "(ab|a)(b*)",
+];
+const TEXTS: [&str; 7] = ["", "abcd", "aab", "babac", "xyzyzz", "ccb", "xxabbb"];
+
+#[test]
+fn captures_report_groups_of_the_leftmost_first_match() {
+    let parser = RegexParser::from("(a|ab)(c|bcd)");
+    let captures = parser.captures("xabcd").unwrap();
+    assert_eq!(captures, vec![Some((1, 5)), Some((1, 2)), Some((2, 5))]);
+
+    let parser = RegexParser::from("((a)|b)*c");
+    // The last iteration was 'b', but the inner group keeps the last 'a'
+    let captures = parser.captures("abc").unwrap();
+    assert_eq!(captures, vec![Some((0, 3)), Some((1, 2)), Some((0, 1))]);
+    assert_eq!(RegexParser::from("(a)|b").captures("b").unwrap()[1], None);
+    assert_eq!(parser.captures("ab"), None);
+}
+
+#[test]
+fn backtracker_agrees_with_pike_vm() {
+    for pattern in PATTERNS {
+        let parser = RegexParser::from(pattern);
+        for text in TEXTS {
+            assert_eq!(
+                parser.get_backtracker().captures(text),
+                parser.get_pikevm().captures(text),
+                "{} on {:?}",
+                pattern,
+                text
+            );
+        }
+    }
+}
+
+#[test]
+fn match_start_agrees_with_dfa() {
+    for pattern in PATTERNS {
+        let parser = RegexParser::from(pattern);
+        for text in TEXTS {
+            let start = parser.captures(text).map(|groups| groups[0].unwrap().0);
+            assert_eq!(start, parser.parse(text), "{} on {:?}", pattern, text);
+        }
+    }
+}
+
+#[test]
+fn visited_bitset_prevents_exponential_blowup() {
+    let parser = RegexParser::from("(a*)*(a*)*c");
+    let text = "a".repeat(2_000);
+    assert_eq!(parser.get_backtracker().captures(&text), None);
+}
+
+#[test]
+fn long_texts_fall_back_to_the_pike_vm() {
+    let parser = RegexParser::from("(a*)b");
+    let program = parser.get_program();
+    let small = Backtracker::with_capacity(program, 64 * program.insts.len());
+    assert_eq!(small.max_haystack_len(), 63);
+
+    let text = "c".repeat(parser.get_backtracker().max_haystack_len()) + "aab";
+    let end = text.chars().count();
+    let captures = parser.captures(&text).unwrap();
+    assert_eq!(captures[1], Some((end - 3, end - 1)));
+}
+
+#[test]
+fn texts_past_the_capacity_run_on_the_pike_vm() {
+    let parser = RegexParser::from("(a*)b");
+    let program = parser.get_program();
+    let small = Backtracker::with_capacity(program, 64 * program.insts.len());
+    let fits = "c".repeat(60) + "aab";
+    let past = "c".repeat(61) + "aab";
+    assert_eq!(fits.chars().count(), small.max_haystack_len());
+    assert_eq!(small.captures(&fits), parser.get_pikevm().captures(&fits));
+    assert_eq!(small.captures(&past), parser.get_pikevm().captures(&past));
+    assert_eq!(small.captures(&past).unwrap()[2], Some(61));
+}
@@ -0,0 +1,104 @@
+use super::RegexParser;
+use crate::automaton::dfa::DFA;
+use crate::regex_parser::parse_ast;
+use crate::thompson::Program;
+use crate::type_defs::State;
+use std::collections::{HashMap, VecDeque};
+
+// =============================================================================
+// REVERSAL / BRZOZOWSKI MINIMIZATION TESTS
+// =============================================================================
+
+fn dfa_for(pattern: &str) -> DFA<char> {
+    DFA::from_efa(&Program::compile(&parse_ast(pattern).unwrap()).to_efa()).unwrap()
+}
+
+// Two DFAs are the same up to renaming when a walk from both starts
+// pairs up states one to one
+fn isomorphic(left: &DFA<char>, right: &DFA<char>) -> bool {
+    if left.automaton.len() != right.automaton.len() {
+        return false;
+    }
+    let left_end = left.end.clone().unwrap();
+    let right_end = right.end.clone().unwrap();
+    let mut pairs: HashMap<State, State> = HashMap::new();
+    let mut queue: VecDeque<(State, State)> = VecDeque::new();
+    pairs.insert(left.start.unwrap(), right.start.unwrap());
+    queue.push_back((left.start.unwrap(), right.start.unwrap()));
+    while let Some((l, r)) = queue.pop_front() {
+        if left_end.get(&l) != right_end.get(&r) {
+            return false;
+        }
+        let (l_transitions, r_transitions) = (&left.automaton[&l], &right.automaton[&r]);
+        if l_transitions.len() != r_transitions.len() {
+            return false;
+        }
+        for (token, l_next) in l_transitions {
+            let Some(r_next) = r_transitions.get(token) else {
+                return false;
+            };
+            match pairs.get(l_next) {
+                Some(paired) if paired != r_next => return false,
+                Some(_) => {}
+                None => {
+                    pairs.insert(*l_next, *r_next);
+                    queue.push_back((*l_next, *r_next));
+                }
+            }
+        }
+    }
+    true
+}
+
+#[test]
+fn reverse_accepts_reversed_strings() {
+    let reversed = dfa_for("abc|de*").reverse();
+    assert_eq!(reversed.parse("cba"), Some(0));
+    assert_eq!(reversed.parse("eeed"), Some(0));
+    assert_eq!(reversed.parse("abc"), None);
+}
+
+#[test]
+fn brzozowski_matches_hopcroft() {
+    let patterns = [
+        "a",
+        "a*",
+        "ab|cd",
+        "a*b|c",
+        "(a|b)*c",
+        "(a|b)*abb",
+        "(a*|b*)*c",
+        "x(a|b*)*y",
+        "(ab|a)(bc|c)",
+        "((a|b)(a|b))*",
+    ];
+    for pattern in patterns {
+        let hopcroft = DFA::minimize_from(dfa_for(pattern)).unwrap();
+        let brzozowski = DFA::minimize_brzozowski(dfa_for(pattern)).unwrap();
+        assert!(isomorphic(&hopcroft, &brzozowski), "pattern {}", pattern);
+    }
+}
+
+#[test]
+fn brzozowski_result_matches_like_the_parser() {
+    let minimal = DFA::minimize_brzozowski(dfa_for("(a|b)*abb")).unwrap();
+    let parser = RegexParser::from("(a|b)*abb");
+    for text in ["abb", "babb", "xxabbx", "ab", "bbba"] {
+        assert_eq!(minimal.parse(text), parser.parse(text), "text {}", text);
+    }
+}
+
+#[test]
+fn brzozowski_minimizes_complements_through_hopcroft() {
+    let complement = dfa_for("ab|c").complement();
+    let minimal = DFA::minimize_brzozowski(complement.clone()).unwrap();
+    assert_eq!(minimal.automaton.len(), complement.automaton.len());
+    for text in ["", "ab", "c", "a", "abc", "zz"] {
+        assert_eq!(
+            minimal.is_full_match(text),
+            complement.is_full_match(text),
+            "{:?}",
+            text
+        );
+    }
+}
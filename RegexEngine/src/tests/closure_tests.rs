@@ -0,0 +1,64 @@
+use super::RegexParser;
+use crate::regex_parser::{parse_ast, Engine};
+use crate::thompson::Program;
+
+// =============================================================================
+// EPSILON CLOSURE / NESTED STAR TESTS
+// =============================================================================
+
+#[test]
+fn closure_is_sorted_and_deduplicated() {
+    let efa = Program::compile(&parse_ast("(a*)*").unwrap()).to_efa();
+    let closure = efa.closure(efa.get_start().unwrap());
+    let mut expected = closure.clone();
+    expected.sort();
+    expected.dedup();
+    assert_eq!(closure, expected);
+    assert!(closure.contains(&efa.get_end().unwrap()));
+}
+
+#[test]
+fn nested_star() {
+    for engine in [Engine::Dfa, Engine::Nfa] {
+        let parser = RegexParser::with_engine("(a*)*", engine);
+        assert_eq!(parser.parse(""), Some(0));
+        assert_eq!(parser.parse("aaa"), Some(0));
+        assert_eq!(parser.parse("b"), Some(0));
+    }
+}
+
+#[test]
+fn nested_star_with_alternation() {
+    for engine in [Engine::Dfa, Engine::Nfa] {
+        let parser = RegexParser::with_engine("(a*|b*)*c", engine);
+        assert_eq!(parser.parse("abbac"), Some(0));
+        assert_eq!(parser.parse("xc"), Some(1));
+        assert_eq!(parser.parse("abba"), None);
+    }
+}
+
+#[test]
+fn deeply_nested_stars() {
+    for engine in [Engine::Dfa, Engine::Nfa] {
+        let parser = RegexParser::with_engine("(((a*)*b*)*)*c", engine);
+        assert_eq!(parser.parse("babac"), Some(0));
+        assert_eq!(parser.parse("xxc"), Some(2));
+        assert_eq!(parser.parse("abab"), None);
+    }
+}
+
+#[test]
+fn nested_star_dfa_has_no_duplicate_states() {
+    // (a*)* and a* accept the same language
+    let nested = RegexParser::from("(a*)*").get_dfa_temp().unwrap();
+    let flat = RegexParser::from("a*").get_dfa_temp().unwrap();
+    assert_eq!(nested.automaton.len(), flat.automaton.len());
+}
+
+#[test]
+fn efa_walk_explores_each_state_once_per_offset() {
+    let parser = RegexParser::with_engine("(a*)*b", Engine::Efa);
+    let text = "a".repeat(20_000);
+    assert_eq!(parser.parse(&text), None);
+    assert_eq!(parser.parse(&(text + "b")), Some(0));
+}
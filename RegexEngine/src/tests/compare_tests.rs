@@ -0,0 +1,72 @@
+use super::RegexParser;
+use crate::regex_parser::{Counterexample, Engine};
+
+// =============================================================================
+// EQUIVALENCE AND INCLUSION TESTS
+// =============================================================================
+
+#[test]
+fn rewritten_patterns_are_equivalent() {
+    let pairs = [
+        ("(a|b)*", "(a*b*)*"),
+        ("a(ba)*", "(ab)*a"),
+        ("(a|b)*abb", "(a|b)*ab(b)"),
+        ("a|b|c", "c|b|a"),
+    ];
+    for (left, right) in pairs {
+        let left = RegexParser::from(left);
+        let right = RegexParser::from(right);
+        assert_eq!(left.equivalent(&right), Ok(()));
+        assert_eq!(right.equivalent(&left), Ok(()));
+    }
+}
+
+#[test]
+fn difference_gives_the_shortest_string() {
+    let left = RegexParser::from("a*");
+    let right = RegexParser::from("aa*");
+    assert_eq!(
+        left.equivalent(&right),
+        Err(Counterexample {
+            text: String::new(),
+            accepted_by_self: true,
+        })
+    );
+    let left = RegexParser::from("(a|b)*abb");
+    let right = RegexParser::from("(a|b)*bb");
+    assert_eq!(
+        left.equivalent(&right),
+        Err(Counterexample {
+            text: "bb".to_string(),
+            accepted_by_self: false,
+        })
+    );
+}
+
+#[test]
+fn inclusion_only_looks_one_way() {
+    let small = RegexParser::from("ab*");
+    let large = RegexParser::from("a(a|b)*");
+    assert_eq!(small.is_subset_of(&large), Ok(()));
+    let counterexample = large.is_subset_of(&small).unwrap_err();
+    assert_eq!(counterexample.text, "aa");
+    assert!(counterexample.accepted_by_self);
+}
+
+#[test]
+fn characters_of_only_one_pattern_are_found() {
+    let left = RegexParser::from("ab|c");
+    let right = RegexParser::from("ab");
+    let counterexample = left.equivalent(&right).unwrap_err();
+    assert_eq!(counterexample.text, "c");
+    assert!(left.is_subset_of(&left).is_ok());
+}
+
+#[test]
+fn engines_without_a_dfa_compare_the_same() {
+    let left = RegexParser::with_engine("(a|b)*c", Engine::PikeVm);
+    let right = RegexParser::with_engine("(b|a)*c", Engine::Nfa);
+    assert!(left.get_dfa_temp().is_none());
+    assert_eq!(left.equivalent(&right), Ok(()));
+    assert_eq!(left.language().automaton.len(), 2);
+}
@@ -0,0 +1,116 @@
+use super::RegexParser;
+use crate::automaton::alphabet::CharClasses;
+use crate::automaton::dense::DenseDFA;
+use crate::automaton::dfa::DFA;
+use std::collections::HashMap;
+
+// =============================================================================
+// COMPLEMENT TESTS
+// =============================================================================
+
+fn dfa_for(pattern: &str) -> DFA<char> {
+    RegexParser::from(pattern).get_dfa_temp().unwrap()
+}
+
+#[test]
+fn complement_flips_every_string() {
+    let pattern = dfa_for("(a|b)*abb");
+    let complement = pattern.complement();
+    for word in ["", "a", "abb", "babb", "abba", "c", "abbc", "xyz"] {
+        assert_ne!(
+            pattern.is_full_match(word),
+            complement.is_full_match(word),
+            "{}",
+            word
+        );
+    }
+}
+
+#[test]
+fn characters_outside_the_alphabet_are_accepted() {
+    let complement = dfa_for("aa*").complement();
+    assert!(!complement.is_full_match("aaa"));
+    assert!(complement.is_full_match(""));
+    assert!(complement.is_full_match("é"));
+    assert!(complement.is_full_match("aaz"));
+    assert!(complement.is_full_match("za"));
+}
+
+#[test]
+fn complement_twice_is_the_original_language() {
+    let pattern = dfa_for("ab|cd*");
+    let twice = pattern.complement().complement();
+    assert_eq!(twice.automaton.len(), pattern.automaton.len());
+    assert!(twice.others.is_empty());
+    for word in ["ab", "c", "cddd", "", "a", "abc", "x"] {
+        assert_eq!(twice.is_full_match(word), pattern.is_full_match(word));
+    }
+}
+
+#[test]
+fn complement_is_minimal() {
+    // "a" splits the strings into empty, "a" and everything else
+    let complement = dfa_for("a").complement();
+    assert_eq!(complement.automaton.len(), 3);
+    assert_eq!(complement.others.len(), 3);
+}
+
+#[test]
+fn difference_with_a_complement_keeps_the_intersection() {
+    let words = dfa_for("(a|b)*");
+    let rest = DFA::difference(&words, &dfa_for("(a|b)*a").complement());
+    for word in ["a", "ba", "bba"] {
+        assert!(rest.is_full_match(word), "{}", word);
+    }
+    for word in ["", "b", "ab", "ca"] {
+        assert!(!rest.is_full_match(word), "{}", word);
+    }
+}
+
+#[test]
+fn dense_complement_uses_class_zero_for_other_characters() {
+    let complement = dfa_for("ab").complement();
+    let classes = CharClasses::from_alphabet(&['a', 'b']).merge_equivalent(&complement);
+    let dense = DenseDFA::new(&complement, classes);
+    let full_match = |text: &str| {
+        let end = text
+            .chars()
+            .fold(dense.start, |state, token| dense.next_state(state, token));
+        dense.is_match(end)
+    };
+    assert!(!full_match("ab"));
+    assert!(full_match("a"));
+    assert!(full_match("abz"));
+    assert!(full_match("zzz"));
+}
+
+#[test]
+fn minimizing_keeps_dying_characters_apart_from_a_live_default() {
+    // Every non-empty string that does not start with an 'a'
+    let dfa = DFA {
+        automaton: HashMap::from([
+            (1, HashMap::from([('a', 2)])),
+            (2, HashMap::new()),
+            (3, HashMap::new()),
+        ]),
+        start: Some(1),
+        end: Some(HashMap::from([(3, vec![0])])),
+        others: HashMap::from([(1, 3), (3, 3)]),
+    };
+    let minimal = DFA::minimize_from(dfa.clone()).unwrap();
+    let complement = dfa.complement();
+    for word in ["", "a", "ab", "b", "ba", "bab", "é"] {
+        assert_eq!(
+            minimal.is_full_match(word),
+            dfa.is_full_match(word),
+            "{}",
+            word
+        );
+        assert_ne!(
+            complement.is_full_match(word),
+            dfa.is_full_match(word),
+            "{}",
+            word
+        );
+    }
+}
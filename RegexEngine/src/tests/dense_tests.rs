@@ -0,0 +1,68 @@
+use super::RegexParser;
+use crate::automaton::dense::{DenseDFA, DEAD};
+
+// =============================================================================
+// DENSE TRANSITION TABLE TESTS
+// =============================================================================
+
+fn dense_for(pattern: &str) -> DenseDFA {
+    RegexParser::from(pattern).get_dense().unwrap().clone()
+}
+
+#[test]
+fn dense_layout_puts_dead_first_and_finals_last() {
+    let dense = dense_for("ab|cd");
+    // Dead state, start, after 'a', after 'c', final
+    assert_eq!(dense.state_count(), 5);
+    assert_eq!(dense.min_match, 4);
+    assert!((0..dense.stride).all(|class| dense.table[class] == DEAD));
+    assert!(!dense.is_match(dense.start));
+
+    let after_a = dense.next_state(dense.start, 'a');
+    assert!(dense.is_match(dense.next_state(after_a, 'b')));
+    assert_eq!(dense.next_state(after_a, 'd'), DEAD);
+}
+
+#[test]
+fn unknown_characters_share_the_dead_column() {
+    let dense = dense_for("a*b");
+    assert_eq!(dense.classes.get('z'), 0);
+    assert_eq!(dense.classes.get('ß'), 0);
+    assert_eq!(dense.next_state(dense.start, 'z'), DEAD);
+}
+
+#[test]
+fn dense_agrees_with_dfa() {
+    let patterns = [
+        "a",
+        "ab|cd",
+        "a*b|c",
+        "(a|b)*c",
+        "xa*y",
+        "a*|b*",
+        "(a|b)*abb",
+    ];
+    let texts = [
+        "", "a", "abef", "xyab", "aaab", "c", "d", "xaaay", "babb", "hel",
+    ];
+    for pattern in patterns {
+        let parser = RegexParser::from(pattern);
+        let dfa = parser.get_dfa_temp().unwrap();
+        for text in texts {
+            assert_eq!(
+                parser.parse(text),
+                dfa.parse(text),
+                "{} on {:?}",
+                pattern,
+                text
+            );
+        }
+    }
+}
+
+#[test]
+fn dense_handles_multibyte_characters() {
+    let parser = RegexParser::from("é*ü");
+    assert_eq!(parser.parse("aéééü"), Some(1));
+    assert_eq!(parser.parse("ééé"), None);
+}
@@ -0,0 +1,138 @@
+use super::RegexParser;
+use crate::automaton::dfa::DFA;
+use crate::derivative::Term;
+use crate::lexer::Lexer;
+use crate::regex_parser::{parse_ast, parse_boolean_ast, ParseError, RegexSet};
+
+// =============================================================================
+// BRZOZOWSKI DERIVATIVE TESTS
+// =============================================================================
+
+#[test]
+fn constructors_normalize_terms() {
+    let a = Term::Char('a');
+    let b = Term::Char('b');
+    assert_eq!(
+        Term::or(vec![a.clone(), b.clone()]),
+        Term::or(vec![b.clone(), a.clone()])
+    );
+    assert_eq!(Term::or(vec![a.clone(), a.clone(), Term::Empty]), a);
+    assert_eq!(Term::concat(Term::Epsilon, a.clone()), a);
+    assert_eq!(Term::concat(a.clone(), Term::Empty), Term::Empty);
+    assert_eq!(Term::and(vec![a.clone(), Term::universal()]), a);
+    assert_eq!(Term::complement(Term::complement(a.clone())), a);
+    assert_eq!(Term::star(Term::star(a.clone())), Term::star(a.clone()));
+    assert_eq!(Term::parse("(ab)c").unwrap(), Term::parse("a(bc)").unwrap());
+}
+
+#[test]
+fn derivatives_match_like_the_thompson_engines() {
+    for pattern in ["(a|b)*abb", "a*b|cd", "(ab|c)*d(e|f)", "a(b|(?:))c"] {
+        let term = Term::parse(pattern).unwrap();
+        let language = RegexParser::from(pattern).language();
+        for word in [
+            "", "abb", "babb", "b", "aab", "cd", "abcd", "cde", "ac", "abc",
+        ] {
+            assert_eq!(
+                term.is_full_match(word),
+                language.is_full_match(word),
+                "{} on {:?}",
+                pattern,
+                word
+            );
+        }
+    }
+}
+
+#[test]
+fn derivative_dfa_is_the_minimal_dfa() {
+    for pattern in ["(a|b)*abb", "a*b|cd", "(ab|c)*d(e|f)"] {
+        let derived = Term::parse(pattern).unwrap().to_dfa();
+        let language = RegexParser::from(pattern).language();
+        assert_eq!(
+            derived.automaton.len(),
+            language.automaton.len(),
+            "{}",
+            pattern
+        );
+        let differs = DFA::shortest_witness(&derived, &language, |l, r| l != r);
+        assert_eq!(differs, None, "{}", pattern);
+    }
+}
+
+#[test]
+fn intersection_and_complement_in_the_syntax() {
+    // Strings of a and b without two a in a row
+    let term = Term::parse("(a|b)*&~((a|b)*aa(a|b)*)").unwrap();
+    let dfa = term.to_dfa();
+    for (word, expected) in [("", true), ("abab", true), ("baab", false), ("abc", false)] {
+        assert_eq!(term.is_full_match(word), expected, "{}", word);
+        assert_eq!(dfa.is_full_match(word), expected, "{}", word);
+    }
+    // Characters the term never mentions go through the default transitions
+    let anything_else = Term::parse("~(ab)").unwrap();
+    assert!(anything_else.is_full_match("xyz"));
+    assert!(!anything_else.is_full_match("ab"));
+    assert!(anything_else.to_dfa().is_full_match("é"));
+}
+
+#[test]
+fn boolean_operators_round_trip_through_the_printer() {
+    for pattern in ["a&~b*", "~(?:ab)c", "(?:a|b)&c*", "(?:~a)*"] {
+        assert_eq!(parse_boolean_ast(pattern).unwrap().to_regex(), pattern);
+    }
+    assert_eq!(
+        parse_boolean_ast("~a*").unwrap(),
+        parse_boolean_ast("~(?:a*)").unwrap()
+    );
+}
+
+#[test]
+fn boolean_operators_are_literals_in_the_standard_syntax() {
+    assert_eq!(RegexParser::from("a&b").find("xa&b"), Some((1, 4)));
+    assert_eq!(RegexParser::from("~a*").find("~aa"), Some((0, 3)));
+    assert_eq!(RegexSet::from(&["a&b", "~"]).matches("a&b~"), vec![0, 1]);
+    let lexer = Lexer::new(&[(0, "a&b"), (1, "~")]);
+    let kinds: Vec<_> = lexer
+        .tokenize("~a&b")
+        .map(|token| token.unwrap().kind)
+        .collect();
+    assert_eq!(kinds, vec![1, 0]);
+    assert_eq!(
+        parse_boolean_ast("a\\&b").unwrap(),
+        parse_ast("a&b").unwrap()
+    );
+}
+
+#[test]
+fn malformed_boolean_patterns_are_errors() {
+    for pattern in ["a~", "~", "&a", "a&", "a(~)", "~*"] {
+        assert_eq!(
+            Term::parse(pattern),
+            Err(ParseError::MissingOperand),
+            "{}",
+            pattern
+        );
+    }
+    assert_eq!(Term::parse("~(a"), Err(ParseError::UnbalancedParenthesis));
+}
+
+#[test]
+fn complements_inside_concatenations_agree_with_the_derivatives() {
+    let words = [
+        "", "a", "b", "ab", "aa", "ba", "aab", "abb", "bab", "é", "aé",
+    ];
+    for pattern in ["~(a~(a&b))", "~(ab)~a", "a~b", "(~a)b|~(ba)", "~(a~(?:))b"] {
+        let term = Term::parse(pattern).unwrap();
+        let dfa = term.to_dfa();
+        for word in words {
+            assert_eq!(
+                dfa.is_full_match(word),
+                term.is_full_match(word),
+                "{} on {:?}",
+                pattern,
+                word
+            );
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use super::RegexParser;
+use crate::automaton::dfa::DFA;
+use crate::derivative::Term;
+use crate::regex_parser::{parse_ast, Engine, ParseError};
+
+// =============================================================================
+// STATE ELIMINATION TESTS
+// =============================================================================
+
+fn round_trip(pattern: &str) -> String {
+    let parser = RegexParser::from(pattern);
+    let regex = parser.language().to_regex().unwrap();
+    let rebuilt = RegexParser::from(&regex);
+    assert_eq!(
+        parser.equivalent(&rebuilt),
+        Ok(()),
+        "{} -> {}",
+        pattern,
+        regex
+    );
+    regex
+}
+
+#[test]
+fn empty_string_syntax() {
+    let parser = RegexParser::from("a(b|(?:))c");
+    assert_eq!(parser.find("xacx"), Some((1, 3)));
+    assert_eq!(parser.find("xabcx"), Some((1, 4)));
+    assert!(RegexParser::from("(?:)").language().is_full_match(""));
+    assert_eq!(parser.equivalent(&RegexParser::from("ac|abc")), Ok(()));
+    assert_eq!(parse_ast("(?:|a)b"), Err(ParseError::MissingOperand));
+    assert_eq!(RegexParser::from("((?:)|a)b").find("b"), Some((0, 1)));
+    for engine in [
+        Engine::Dfa,
+        Engine::Nfa,
+        Engine::LazyDfa,
+        Engine::Efa,
+        Engine::PikeVm,
+        Engine::Backtrack,
+    ] {
+        let parser = RegexParser::with_engine("xa(b|(?:))*c", engine);
+        assert_eq!(parser.parse("zxac"), Some(1), "{:?}", engine);
+        assert_eq!(parser.parse("xabbc"), Some(0), "{:?}", engine);
+        assert_eq!(parser.parse("xa"), None, "{:?}", engine);
+    }
+}
+
+#[test]
+fn epsilon_is_a_literal_character() {
+    let parser = RegexParser::from("ε");
+    assert_eq!(parser.find("ε"), Some((0, 1)));
+    assert_eq!(parser.find("abc"), None);
+    assert_eq!(RegexParser::from("aε*").find("aεε"), Some((0, 3)));
+    assert_eq!(parse_ast("\\ε").unwrap(), parse_ast("ε").unwrap());
+    assert_ne!(parse_ast("ε").unwrap(), parse_ast("(?:)").unwrap());
+    assert_eq!(parse_ast("aε|(?:)").unwrap().to_regex(), "aε|(?:)");
+    assert_eq!(round_trip("ε|b"), "b|ε");
+}
+
+#[test]
+fn simple_languages_come_back_short() {
+    assert_eq!(round_trip("a"), "a");
+    assert_eq!(round_trip("ab"), "ab");
+    assert_eq!(round_trip("a*"), "a*");
+    assert_eq!(round_trip("a|b"), "a|b");
+}
+
+#[test]
+fn patterns_survive_a_round_trip() {
+    for pattern in [
+        "(a|b)*abb",
+        "a*b|cd",
+        "(ab|c)*d(e|f)",
+        "a(ba)*",
+        "(a|b)(c|d)*e|f",
+        "a*(b|(?:))",
+    ] {
+        round_trip(pattern);
+    }
+}
+
+#[test]
+fn products_become_patterns() {
+    let both = DFA::intersect(
+        &RegexParser::from("(a|b)*a").language(),
+        &RegexParser::from("a(a|b)*").language(),
+    );
+    let regex = both.to_regex().unwrap();
+    let expected = RegexParser::from("a|a(a|b)*a");
+    assert_eq!(RegexParser::from(&regex).equivalent(&expected), Ok(()));
+}
+
+#[test]
+fn empty_language_has_no_pattern() {
+    let pattern = RegexParser::from("ab").language();
+    assert_eq!(DFA::difference(&pattern, &pattern).to_regex(), None);
+    // The empty string alone is still a language
+    assert_eq!(
+        RegexParser::from("(?:)").language().to_regex(),
+        Some("(?:)".to_string())
+    );
+}
+
+#[test]
+fn default_transitions_are_written_with_complements() {
+    let complement = RegexParser::from("ab|c").language().complement();
+    let regex = complement.to_regex().unwrap();
+    let rebuilt = Term::parse(&regex).unwrap().to_dfa();
+    assert_eq!(
+        DFA::shortest_witness(&complement, &rebuilt, |l, r| l != r),
+        None,
+        "{}",
+        regex
+    );
+    assert!(RegexParser::from("a")
+        .language()
+        .complement()
+        .to_regex()
+        .is_some());
+    let regex = Term::parse("~a").unwrap().to_dfa().to_regex().unwrap();
+    let rebuilt = Term::parse(&regex).unwrap();
+    for text in ["", "a", "b", "aa", "ab"] {
+        assert_eq!(
+            rebuilt.is_full_match(text),
+            text != "a",
+            "{} on {:?}",
+            regex,
+            text
+        );
+    }
+}
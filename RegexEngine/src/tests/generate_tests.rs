@@ -0,0 +1,63 @@
+use super::RegexParser;
+use crate::automaton::dfa::{SplitMix64, DFA};
+use std::collections::HashMap;
+
+// =============================================================================
+// ENUMERATION AND SAMPLING TESTS
+// =============================================================================
+
+#[test]
+fn strings_come_in_shortlex_order() {
+    let parser = RegexParser::from("(a|b)*c");
+    assert_eq!(
+        parser.examples(8),
+        vec!["c", "ac", "bc", "aac", "abc", "bac", "bbc", "aaac"]
+    );
+}
+
+#[test]
+fn finite_languages_run_out() {
+    let parser = RegexParser::from("ab|b|a(b|c)");
+    let all: Vec<String> = parser.language().strings().collect();
+    assert_eq!(all, vec!["b", "ab", "ac"]);
+    let nothing = RegexParser::from("a").language();
+    let nothing = DFA::difference(&nothing, &nothing);
+    assert_eq!(nothing.strings().count(), 0);
+}
+
+#[test]
+fn enumeration_skips_dead_branches() {
+    let parser = RegexParser::from("a*b|cd");
+    let strings = parser.examples(4);
+    assert_eq!(strings, vec!["b", "ab", "cd", "aab"]);
+}
+
+#[test]
+fn fixtures_match_the_pattern() {
+    let parser = RegexParser::from("(ab|c)*d(e|f)");
+    let fixtures = parser.fixtures(50, 8, 7);
+    assert_eq!(fixtures.len(), 50);
+    for fixture in fixtures.iter() {
+        assert!(fixture.chars().count() <= 8);
+        assert!(parser.language().is_full_match(fixture), "{}", fixture);
+    }
+    assert_eq!(parser.fixtures(50, 8, 7), fixtures);
+    assert!(RegexParser::from("abc").fixtures(3, 2, 0).is_empty());
+}
+
+#[test]
+fn sampling_is_uniform_over_strings() {
+    // "a" is one string out of three, so it must not get half of the draws
+    let language = RegexParser::from("a|b(a|b)").language();
+    let mut rng = SplitMix64::new(1);
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    for _ in 0..4000 {
+        *seen
+            .entry(language.sample(2, &mut rng).unwrap())
+            .or_default() += 1;
+    }
+    assert_eq!(seen.len(), 3);
+    for count in seen.values() {
+        assert!((1100..1600).contains(count), "{:?}", seen);
+    }
+}
@@ -0,0 +1,113 @@
+use super::RegexParser;
+use crate::automaton::alphabet::CharClasses;
+use crate::automaton::lazy_dfa::LazyDFA;
+use crate::regex_parser::{parse_ast, Engine};
+use crate::thompson::Program;
+
+// =============================================================================
+// LAZY DFA TESTS
+// =============================================================================
+
+// (a|b)*a(a|b)(a|b)... whose full DFA needs 2^(n+1) states
+fn exponential_pattern(n: usize) -> String {
+    let mut pattern = String::from("(a|b)*a");
+    for _ in 0..n {
+        pattern.push_str("(a|b)");
+    }
+    pattern
+}
+
+fn lazy_for(pattern: &str, budget: usize, max_clears: usize) -> LazyDFA {
+    let tree = parse_ast(pattern).unwrap();
+    let efa = Program::compile(&tree).to_efa();
+    LazyDFA::with_budget(&efa, CharClasses::from_ast(&tree), budget, max_clears)
+}
+
+#[test]
+fn lazy_engine_agrees_with_dfa() {
+    let patterns = [
+        "a",
+        "ab|cd",
+        "a*b|c",
+        "(a|b)*c",
+        "xa*y",
+        "a*|b*",
+        "(a|b)*abb",
+    ];
+    let texts = [
+        "", "a", "abef", "xyab", "aaab", "c", "d", "xaaay", "babb", "hel",
+    ];
+    for pattern in patterns {
+        let dfa = RegexParser::from(pattern);
+        let lazy = RegexParser::with_engine(pattern, Engine::LazyDfa);
+        for text in texts {
+            assert_eq!(
+                lazy.parse(text),
+                dfa.parse(text),
+                "{} on {:?}",
+                pattern,
+                text
+            );
+        }
+    }
+}
+
+#[test]
+fn lazy_engine_only_builds_visited_states() {
+    let lazy = lazy_for(&exponential_pattern(20), 1 << 20, 8);
+    assert_eq!(lazy.parse("bbbbab"), None);
+    // Far fewer than the 2^21 states of the full construction
+    assert!(lazy.cached_states() < 64);
+    assert_eq!(lazy.cache_clears(), 0);
+}
+
+#[test]
+fn lazy_engine_clears_the_cache_within_budget() {
+    let pattern = exponential_pattern(4);
+    let lazy = lazy_for(&pattern, 700, 1000);
+    let text = "bbbbabab";
+    assert_eq!(lazy.parse(text), RegexParser::from(&pattern).parse(text));
+    assert!(lazy.cache_clears() > 0);
+}
+
+#[test]
+fn lazy_engine_falls_back_to_the_nfa() {
+    let pattern = exponential_pattern(6);
+    let lazy = lazy_for(&pattern, 1024, 1);
+    let text = "bbabbbaababbbabababbbbbbbbbbbbaababaabb";
+    assert_eq!(lazy.parse(text), RegexParser::from(&pattern).parse(text));
+    assert_eq!(lazy.cache_clears(), 1);
+}
+
+#[test]
+fn lazy_engine_scans_long_haystacks_once() {
+    let lazy = RegexParser::with_engine("a*b", Engine::LazyDfa);
+    let text = "a".repeat(200_000);
+    assert_eq!(lazy.parse(&text), None);
+    // Every start in the prefix stays alive until the 'c'
+    assert_eq!(lazy.parse(&(text + "cb")), Some(200_001));
+}
+
+#[test]
+fn lazy_engine_finds_starts_of_matches_ending_after_the_earliest_one() {
+    // "c" ends first, but the match of "abcd" starts further left
+    let lazy = RegexParser::with_engine("abcd|c", Engine::LazyDfa);
+    assert_eq!(lazy.parse("xabcd"), Some(1));
+    assert_eq!(lazy.parse("xabce"), Some(3));
+    assert_eq!(lazy.parse("αβabcd"), Some(2));
+}
+
+#[test]
+fn parsers_can_be_shared_across_threads() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<LazyDFA>();
+    assert_send_sync::<RegexParser>();
+
+    let parser = RegexParser::with_engine(&exponential_pattern(12), Engine::LazyDfa);
+    let text = "b".repeat(50) + "a" + &"b".repeat(12);
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| assert_eq!(parser.parse(&text), Some(0)));
+        }
+    });
+}
@@ -0,0 +1,105 @@
+use crate::lexer::{LexError, Lexer, Token};
+
+// =============================================================================
+// LEXER TESTS
+// =============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    If,
+    Ident,
+    Number,
+    Arrow,
+    Minus,
+    Space,
+}
+
+fn lexer() -> Lexer<Kind> {
+    Lexer::new(&[
+        (Kind::If, "if"),
+        (Kind::Ident, "(i|f|x|y)(i|f|x|y|0|1)*"),
+        (Kind::Number, "(0|1)(0|1)*"),
+        (Kind::Arrow, "->"),
+        (Kind::Minus, "-"),
+        (Kind::Space, "  *"),
+    ])
+}
+
+fn kinds(text: &str, lexer: &Lexer<Kind>) -> Vec<Result<Kind, LexError>> {
+    lexer
+        .tokenize(text)
+        .map(|token| token.map(|token| token.kind))
+        .collect()
+}
+
+#[test]
+fn longest_match_wins() {
+    let lexer = lexer().skip(&[Kind::Space]);
+    let tokens: Vec<_> = lexer.tokenize("iff -> x1").collect();
+    assert_eq!(
+        tokens,
+        vec![
+            Ok(Token {
+                kind: Kind::Ident,
+                span: (0, 3)
+            }),
+            Ok(Token {
+                kind: Kind::Arrow,
+                span: (4, 6)
+            }),
+            Ok(Token {
+                kind: Kind::Ident,
+                span: (7, 9)
+            }),
+        ]
+    );
+}
+
+#[test]
+fn earlier_rules_win_ties() {
+    let lexer = lexer().skip(&[Kind::Space]);
+    assert_eq!(kinds("if", &lexer), vec![Ok(Kind::If)]);
+    assert_eq!(kinds("if1", &lexer), vec![Ok(Kind::Ident)]);
+    assert_eq!(
+        kinds("--> 10", &lexer),
+        vec![Ok(Kind::Minus), Ok(Kind::Arrow), Ok(Kind::Number)]
+    );
+}
+
+#[test]
+fn skipped_kinds_are_not_yielded() {
+    assert_eq!(
+        kinds("x  y", &lexer()),
+        vec![Ok(Kind::Ident), Ok(Kind::Space), Ok(Kind::Ident)]
+    );
+    assert_eq!(kinds("x  y", &lexer().skip(&[Kind::Space])).len(), 2);
+}
+
+#[test]
+fn unmatched_input_is_reported_and_skipped() {
+    let lexer = lexer().skip(&[Kind::Space]);
+    let tokens: Vec<_> = lexer.tokenize("x ?é! 10 #").collect();
+    assert_eq!(tokens[0].as_ref().unwrap().kind, Kind::Ident);
+    assert_eq!(tokens[1], Err(LexError { span: (2, 5) }));
+    assert_eq!(tokens[2].as_ref().unwrap().span, (6, 8));
+    assert_eq!(tokens[3], Err(LexError { span: (9, 10) }));
+    assert_eq!(tokens.len(), 4);
+}
+
+#[test]
+fn long_unmatched_prefix_is_skipped_in_linear_time() {
+    let lexer = Lexer::new(&[(Kind::Ident, "a*b"), (Kind::Minus, "-")]);
+    let text = "a".repeat(50_000) + "-";
+    let tokens: Vec<_> = lexer.tokenize(&text).collect();
+    assert_eq!(tokens[0], Err(LexError { span: (0, 50_000) }));
+    assert_eq!(tokens[1].as_ref().unwrap().kind, Kind::Minus);
+    assert_eq!(tokens.len(), 2);
+}
+
+#[test]
+fn short_tokens_under_a_long_failing_rule_are_lexed_in_linear_time() {
+    // Every token walks to the end of the text looking for a 'b'
+    let lexer = Lexer::new(&[(Kind::Arrow, "a*b"), (Kind::Ident, "a")]);
+    let text = "a".repeat(50_000);
+    assert_eq!(lexer.tokenize(&text).count(), 50_000);
+}
@@ -0,0 +1,84 @@
+use super::RegexParser;
+use crate::regex_parser::{parse_ast, Engine, Literals};
+
+// =============================================================================
+// LITERAL EXTRACTION AND PREFILTER TESTS
+// =============================================================================
+
+fn literals_of(pattern: &str) -> Literals {
+    Literals::of(&parse_ast(pattern).unwrap())
+}
+
+#[test]
+fn prefix_and_suffix_come_from_the_ends() {
+    let literals = literals_of("ERROR: (a|b)*c");
+    assert_eq!(literals.prefix, "ERROR: ");
+    assert_eq!(literals.suffix, "c");
+    assert_eq!(literals.inner, "ERROR: ");
+    assert_eq!(literals.exact(), None);
+
+    let literals = literals_of("(abc|abd)e");
+    assert_eq!(
+        (literals.prefix.as_str(), literals.suffix.as_str()),
+        ("ab", "e")
+    );
+}
+
+#[test]
+fn inner_literals_span_the_junctions() {
+    let literals = literals_of("a*(xab|yab)cd(e|f)");
+    assert_eq!(literals.prefix, "");
+    assert_eq!(literals.suffix, "");
+    assert_eq!(literals.inner, "abcd");
+    assert!(literals_of("a*|b").is_empty());
+}
+
+#[test]
+fn exact_literals_are_recognised_through_groups() {
+    assert_eq!(literals_of("(ab)c").exact(), Some("abc"));
+    assert_eq!(literals_of("ab|ab").exact(), Some("ab"));
+    assert_eq!(literals_of("ab|ac").exact(), None);
+    assert_eq!(RegexParser::from("(ab)c").find("xabc"), Some((1, 4)));
+}
+
+#[test]
+fn prefilter_agrees_with_the_plain_dfa() {
+    let patterns = ["ERROR: (a|b)*c", "a*(xab|yab)cd", "(abc|abd)e", "x(y|z)*"];
+    let texts = [
+        "",
+        "ERROR: abc",
+        "aaxabcd",
+        "ERROR: ERROR: ac",
+        "abdabe",
+        "yxzy",
+    ];
+    for pattern in patterns {
+        let auto = RegexParser::from(pattern);
+        let dfa = RegexParser::with_engine(pattern, Engine::Dfa);
+        for text in texts {
+            assert_eq!(
+                auto.parse(text),
+                dfa.parse(text),
+                "{} on {:?}",
+                pattern,
+                text
+            );
+            assert_eq!(
+                auto.is_match(text),
+                dfa.is_match(text),
+                "{} on {:?}",
+                pattern,
+                text
+            );
+        }
+    }
+}
+
+#[test]
+fn prefilter_skips_to_the_first_candidate() {
+    let parser = RegexParser::from("ERROR: (a|b)*c");
+    let text = "α".repeat(100_000) + "ERROR: abac";
+    assert_eq!(parser.parse(&text), Some(100_000));
+    assert_eq!(parser.find(&text), Some((100_000, 100_011)));
+    assert!(!parser.is_match(&"ERROR".repeat(1_000)));
+}
@@ -0,0 +1,111 @@
+use super::RegexParser;
+use crate::automaton::dfa::DFA;
+use crate::regex_parser::{Engine, MAX_DFA_STATES};
+
+// =============================================================================
+// META ENGINE TESTS
+// =============================================================================
+
+const ENGINES: [Engine; 7] = [
+    Engine::Auto,
+    Engine::Dfa,
+    Engine::Nfa,
+    Engine::LazyDfa,
+    Engine::Efa,
+    Engine::PikeVm,
+    Engine::Backtrack,
+];
+
+#[test]
+fn analysis_describes_the_pattern() {
+    let analysis = RegexParser::from("abc").get_analysis().clone();
+    assert_eq!(analysis.literal, Some("abc".to_string()));
+    assert!(!analysis.has_captures && !analysis.matches_empty && analysis.one_pass);
+
+    let analysis = RegexParser::from("(a|ab)*c").get_analysis().clone();
+    assert_eq!(analysis.literal, None);
+    assert!(analysis.has_captures && !analysis.matches_empty && !analysis.one_pass);
+    assert!(analysis.dfa_states.is_some());
+    assert!(RegexParser::from("a*|b").get_analysis().matches_empty);
+}
+
+#[test]
+fn every_engine_answers_the_same() {
+    let patterns = [
+        "abc",
+        "(a|ab)(c|bcd)",
+        "(a*)*b",
+        "((a)|b)*c",
+        "x(y|z)*",
+        "a*",
+    ];
+    let texts = ["", "abcd", "zzabc", "aab", "babac", "xyzy"];
+    for pattern in patterns {
+        let auto = RegexParser::from(pattern);
+        for engine in ENGINES {
+            let parser = RegexParser::with_engine(pattern, engine);
+            assert_eq!(parser.get_engine(), engine);
+            for text in texts {
+                let context = format!("{} on {:?} with {:?}", pattern, text, engine);
+                assert_eq!(parser.is_match(text), auto.is_match(text), "{}", context);
+                assert_eq!(parser.parse(text), auto.parse(text), "{}", context);
+                assert_eq!(parser.find(text), auto.find(text), "{}", context);
+                assert_eq!(parser.captures(text), auto.captures(text), "{}", context);
+            }
+        }
+    }
+}
+
+#[test]
+fn literal_patterns_skip_the_automata() {
+    let parser = RegexParser::from("ab");
+    assert_eq!(parser.find("ααxab"), Some((3, 5)));
+    assert_eq!(parser.captures("xab").unwrap(), vec![Some((1, 3))]);
+    assert!(!parser.is_match("ba"));
+}
+
+#[test]
+fn forced_one_pass_engine_scans_every_start() {
+    let parser = RegexParser::with_engine("(a|b)*c", Engine::OnePass);
+    assert_eq!(parser.find("xxabc"), Some((2, 5)));
+    assert_eq!(parser.parse("xx"), None);
+}
+
+#[test]
+fn oversized_dfa_falls_back_to_the_lazy_dfa() {
+    // The last 14 characters have to be remembered
+    let pattern = "(a|b)*a".to_string() + &"(a|b)".repeat(13);
+    let parser = RegexParser::from(&pattern);
+    assert_eq!(parser.get_analysis().dfa_states, Some(MAX_DFA_STATES + 1));
+    assert!(parser.get_dense().is_none());
+
+    let text = "b".repeat(20) + "a" + &"b".repeat(13);
+    assert!(parser.is_match(&text));
+    assert_eq!(parser.parse(&text), Some(0));
+    assert!(!parser.is_match(&"b".repeat(30)));
+}
+
+#[test]
+fn subset_construction_stops_at_the_state_limit() {
+    // 2^21 states in full, far too many to build before checking
+    let pattern = "(a|b)*a".to_string() + &"(a|b)".repeat(20);
+    let efa = RegexParser::with_engine(&pattern, Engine::Nfa).get_efa_temp();
+    assert!(DFA::from_efa_bounded(&efa, 100).is_none());
+    let small = RegexParser::with_engine("(a|b)*abb", Engine::Nfa).get_efa_temp();
+    assert!(DFA::from_efa_bounded(&small, 100).is_some());
+}
+
+#[test]
+fn small_dfa_with_huge_search_automata_falls_back_to_the_lazy_dfa() {
+    // Few anchored states, but exponentially many in reverse and unanchored
+    let pattern = "(a|b)".repeat(14) + "a(a|b)*";
+    let nfa = RegexParser::with_engine(&pattern, Engine::Nfa);
+    assert!(DFA::from_efa_bounded(&nfa.get_efa_temp(), 100).is_some());
+
+    let parser = RegexParser::from(&pattern);
+    assert_eq!(parser.get_analysis().dfa_states, Some(MAX_DFA_STATES + 1));
+    assert!(parser.get_forward().is_none() && parser.get_reverse().is_none());
+    let text = "b".repeat(20) + "a";
+    assert_eq!(parser.parse(&text), Some(6));
+    assert_eq!(parser.parse(&"b".repeat(30)), None);
+}
@@ -0,0 +1,82 @@
+use super::RegexParser;
+use crate::automaton::dfa::DFA;
+use crate::type_defs::State;
+use std::collections::HashMap;
+
+// =============================================================================
+// HOPCROFT MINIMIZATION TESTS
+// =============================================================================
+
+fn states_of(pattern: &str) -> usize {
+    RegexParser::from(pattern)
+        .get_dfa_temp()
+        .unwrap()
+        .automaton
+        .len()
+}
+
+fn dfa_from(transitions: &[(State, char, State)], start: State, end: &[State]) -> DFA<char> {
+    let mut automaton: HashMap<State, HashMap<char, State>> = HashMap::new();
+    for (from, token, to) in transitions {
+        automaton.entry(*from).or_default().insert(*token, *to);
+        automaton.entry(*to).or_default();
+    }
+    DFA {
+        automaton,
+        start: Some(start),
+        end: Some(end.iter().map(|state| (*state, vec![0])).collect()),
+        others: HashMap::new(),
+    }
+}
+
+#[test]
+fn minimal_sizes_of_known_patterns() {
+    assert_eq!(states_of("a*"), 1);
+    assert_eq!(states_of("(a|b)*"), 1);
+    assert_eq!(states_of("(a|b)*c"), 2);
+    assert_eq!(states_of("a*b|c"), 3);
+    assert_eq!(states_of("ab|cd"), 4);
+    assert_eq!(states_of("(a|b)*abb"), 4);
+}
+
+#[test]
+fn equivalent_states_are_merged() {
+    // 2 and 3 both accept exactly "c" and can be merged
+    let dfa = dfa_from(
+        &[(1, 'a', 2), (1, 'b', 3), (2, 'c', 4), (3, 'c', 5)],
+        1,
+        &[4, 5],
+    );
+    let minimal = DFA::minimize_from(dfa).unwrap();
+    assert_eq!(minimal.automaton.len(), 3);
+    assert_eq!(minimal.parse("xbc"), Some(1));
+}
+
+#[test]
+fn states_without_transitions_are_not_merged_with_live_ones() {
+    // 3 has no way to reach a final state, unlike 2, so the two must stay apart
+    let dfa = dfa_from(&[(1, 'a', 2), (1, 'b', 3), (2, 'a', 4)], 1, &[4]);
+    let minimal = DFA::minimize_from(dfa).unwrap();
+    assert_eq!(minimal.automaton.len(), 3);
+    assert_eq!(minimal.parse("ba"), None);
+    assert_eq!(minimal.parse("aa"), Some(0));
+}
+
+#[test]
+fn dead_and_unreachable_states_are_dropped() {
+    let dfa = dfa_from(
+        &[(1, 'a', 2), (1, 'b', 3), (3, 'b', 3), (5, 'a', 2)],
+        1,
+        &[2],
+    );
+    let minimal = DFA::minimize_from(dfa).unwrap();
+    assert_eq!(minimal.automaton.len(), 2);
+}
+
+#[test]
+fn empty_language_keeps_only_the_start() {
+    let dfa = dfa_from(&[(1, 'a', 2), (2, 'a', 1)], 1, &[]);
+    let minimal = DFA::minimize_from(dfa).unwrap();
+    assert_eq!(minimal.automaton.len(), 1);
+    assert!(minimal.end.unwrap().is_empty());
+}
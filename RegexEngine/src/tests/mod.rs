@@ -1,8 +1,34 @@
 // Module declarations for organized test files
 use crate::RegexParser;
+pub mod aho_corasick_tests;
+pub mod alphabet_tests;
+pub mod analysis_tests;
+pub mod backtrack_tests;
 pub mod basic_tests;
+pub mod brzozowski_tests;
+pub mod closure_tests;
+pub mod compare_tests;
+pub mod complement_tests;
 pub mod complex_tests;
 pub mod concatenation_tests;
+pub mod dense_tests;
+pub mod derivative_tests;
 pub mod edge_case_tests;
+pub mod elimination_tests;
+pub mod generate_tests;
+pub mod lazy_dfa_tests;
+pub mod lexer_tests;
+pub mod literals_tests;
+pub mod meta_tests;
+pub mod minimize_tests;
+pub mod nfa_tests;
+pub mod onepass_tests;
 pub mod or_tests;
+pub mod parse_error_tests;
+pub mod printer_tests;
+pub mod product_tests;
+pub mod reverse_tests;
+pub mod search_tests;
+pub mod set_tests;
 pub mod star_tests;
+pub mod thompson_tests;
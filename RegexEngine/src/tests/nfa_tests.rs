@@ -0,0 +1,66 @@
+use super::RegexParser;
+use crate::automaton::dfa::DFA;
+use crate::automaton::nfa::NFA;
+use crate::regex_parser::{parse_ast, Engine};
+use crate::thompson::Program;
+
+// =============================================================================
+// NFA ENGINE TESTS
+// =============================================================================
+
+fn nfa_for(regex: &str) -> NFA<char> {
+    NFA::from_efa(&Program::compile(&parse_ast(regex).unwrap()).to_efa()).unwrap()
+}
+
+#[test]
+fn nfa_has_no_epsilon_states() {
+    // Only the start, the shared target of 'a' and 'b', and the target of 'c' survive
+    let nfa = nfa_for("(a|b)*c");
+    assert_eq!(nfa.automaton.len(), 3);
+}
+
+#[test]
+fn nfa_engine_agrees_with_dfa() {
+    let patterns = ["a", "ab|cd", "a*b|c", "(a|b)*c", "xa*y", "a*|b*", "hello"];
+    let texts = [
+        "",
+        "a",
+        "abef",
+        "xyab",
+        "aaab",
+        "c",
+        "d",
+        "xaaay",
+        "the hello",
+        "hel",
+    ];
+    for pattern in patterns {
+        let dfa = RegexParser::from(pattern);
+        let nfa = RegexParser::with_engine(pattern, Engine::Nfa);
+        for text in texts {
+            assert_eq!(
+                nfa.parse(text),
+                dfa.parse(text),
+                "{} on {:?}",
+                pattern,
+                text
+            );
+        }
+    }
+}
+
+#[test]
+fn nfa_reports_leftmost_start() {
+    let parser = RegexParser::with_engine("abc|b", Engine::Nfa);
+    assert_eq!(parser.parse("xabc"), Some(1));
+    assert_eq!(parser.parse("xbc"), Some(1));
+    assert!(parser.get_dfa_temp().is_none());
+}
+
+#[test]
+fn nfa_converts_to_dfa() {
+    let nfa = nfa_for("(a|b)*c");
+    let dfa = DFA::from_nfa(&nfa).unwrap();
+    assert_eq!(dfa.parse("xxabac"), Some(2));
+    assert_eq!(dfa.parse("abab"), None);
+}
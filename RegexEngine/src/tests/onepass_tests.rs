@@ -0,0 +1,91 @@
+use super::RegexParser;
+use crate::automaton::onepass::OnePass;
+
+// =============================================================================
+// ONE-PASS DFA TESTS
+// =============================================================================
+
+fn is_one_pass(pattern: &str) -> bool {
+    OnePass::is_one_pass(RegexParser::from(pattern).get_program())
+}
+
+#[test]
+fn detector_accepts_unambiguous_patterns() {
+    for pattern in ["(aa*)-(bb*)", "ab|cd", "(a|b)*c", "a(bc)*", "x(y|z)*"] {
+        assert!(is_one_pass(pattern), "{}", pattern);
+    }
+}
+
+#[test]
+fn detector_rejects_ambiguous_patterns() {
+    // Two threads would consume the same character, or an empty loop
+    for pattern in ["a|ab", "(a*)(a*)", "x(y|yz)*z", "(a*)*", "a*a"] {
+        assert!(!is_one_pass(pattern), "{}", pattern);
+    }
+}
+
+#[test]
+fn captures_are_recorded_on_transitions() {
+    let parser = RegexParser::from("(aa*)-(bb*)");
+    assert!(parser.get_onepass().is_some());
+    let captures = parser.captures("xxaa-bbbc").unwrap();
+    assert_eq!(captures, vec![Some((2, 8)), Some((2, 4)), Some((5, 8))]);
+    assert_eq!(parser.captures("aa-"), None);
+}
+
+#[test]
+fn dying_thread_falls_back_to_the_last_match() {
+    let parser = RegexParser::from("a(bc)*");
+    let onepass = parser.get_onepass().unwrap();
+    let slots = onepass.captures_at("abcb", 0).unwrap();
+    assert_eq!(slots, vec![Some(0), Some(3), Some(1), Some(3)]);
+    assert_eq!(
+        onepass.captures_at("xa", 1).unwrap()[..2],
+        [Some(1), Some(2)]
+    );
+}
+
+#[test]
+fn one_pass_agrees_with_backtracker() {
+    let patterns = ["(aa*)-(bb*)", "ab|cd", "((a)|b)*c", "a(bc)*", "x(y|z)*"];
+    let texts = ["", "aa-b", "cdab", "babac", "abcbc", "xyzzy", "-a-b"];
+    for pattern in patterns {
+        let parser = RegexParser::from(pattern);
+        let backtracker = parser.get_backtracker();
+        let onepass = parser.get_onepass().unwrap();
+        for text in texts {
+            let expected = backtracker.captures(text);
+            let start = parser.parse(text);
+            let found = start.and_then(|start| onepass.captures_at(text, start));
+            assert_eq!(found, expected, "{} on {:?}", pattern, text);
+        }
+    }
+}
+
+#[test]
+fn unanchored_search_agrees_with_the_pike_vm() {
+    let patterns = ["(aa*)-(bb*)", "ab|cd", "(a|b)*c", "a(bc)*", "x(y|z)*"];
+    let texts = ["", "xaa-bb", "a-", "cabcd", "bbac", "abcbcx", "xxyz", "zz"];
+    for pattern in patterns {
+        let parser = RegexParser::from(pattern);
+        let onepass = parser.get_onepass().unwrap();
+        for text in texts {
+            assert_eq!(
+                onepass.captures(text),
+                parser.get_pikevm().captures(text),
+                "{} on {:?}",
+                pattern,
+                text
+            );
+        }
+    }
+}
+
+#[test]
+fn forced_one_pass_engine_scans_long_haystacks_once() {
+    use crate::regex_parser::Engine;
+    let parser = RegexParser::with_engine("(aa*)-(bb*)", Engine::OnePass);
+    let text = "a".repeat(100_000);
+    assert_eq!(parser.find(&text), None);
+    assert_eq!(parser.find(&(text + "-b")), Some((0, 100_002)));
+}
@@ -0,0 +1,45 @@
+use super::RegexParser;
+use crate::regex_parser::{parse_ast, ParseError};
+use crate::tree::Node;
+
+// =============================================================================
+// PARSE ERROR TESTS
+// =============================================================================
+
+#[test]
+fn empty_groups_do_not_swallow_the_previous_operand() {
+    assert_eq!(parse_ast("a()"), Err(ParseError::MissingOperand));
+    assert_eq!(parse_ast("()"), Err(ParseError::MissingOperand));
+    // An empty non-capturing group is the empty string instead
+    let empty = Node::with_lr('·', Node::leaf('a'), Node::epsilon());
+    assert_eq!(parse_ast("a(?:)"), Ok(empty));
+}
+
+#[test]
+fn operators_only_take_operands_of_their_own_group() {
+    assert_eq!(parse_ast("a(|b)"), Err(ParseError::MissingOperand));
+    assert_eq!(parse_ast("a(*)"), Err(ParseError::MissingOperand));
+    assert_eq!(parse_ast("|a"), Err(ParseError::MissingOperand));
+    assert_eq!(parse_ast("*"), Err(ParseError::MissingOperand));
+    assert_eq!(parse_ast(""), Err(ParseError::MissingOperand));
+}
+
+#[test]
+fn unbalanced_parentheses_are_reported() {
+    assert_eq!(parse_ast("a)"), Err(ParseError::UnbalancedParenthesis));
+    assert_eq!(parse_ast("(a"), Err(ParseError::UnbalancedParenthesis));
+    assert_eq!(parse_ast("(?:a"), Err(ParseError::UnbalancedParenthesis));
+}
+
+#[test]
+fn dangling_escape_is_reported() {
+    assert_eq!(parse_ast("a\\"), Err(ParseError::DanglingEscape));
+    assert!(RegexParser::new("a\\").is_err());
+}
+
+#[test]
+fn valid_groups_still_parse() {
+    assert_eq!(parse_ast("a(b)").unwrap().to_regex(), "a(b)");
+    assert_eq!(parse_ast("(a|b)*").unwrap().to_regex(), "(a|b)*");
+    assert_eq!(RegexParser::new("a(b|c)").unwrap().parse("xac"), Some(1));
+}
@@ -0,0 +1,80 @@
+use crate::regex_parser::parse_ast;
+use crate::tree::Node;
+
+// =============================================================================
+// PRETTY-PRINTER / ROUND-TRIP TESTS
+// =============================================================================
+
+fn assert_round_trip(pattern: &str) {
+    let ast = parse_ast(pattern).unwrap();
+    assert_eq!(
+        parse_ast(&ast.to_regex()).unwrap(),
+        ast,
+        "pattern {}",
+        pattern
+    );
+}
+
+#[test]
+fn printer_drops_redundant_parentheses() {
+    assert_eq!(parse_ast("(?:(?:a))|(?:b)").unwrap().to_regex(), "a|b");
+    assert_eq!(parse_ast("(?:ab)c").unwrap().to_regex(), "abc");
+    assert_eq!(parse_ast("(?:a*)*").unwrap().to_regex(), "a**");
+}
+
+#[test]
+fn printer_keeps_required_parentheses() {
+    assert_eq!(parse_ast("(a|b)*c").unwrap().to_regex(), "(a|b)*c");
+    assert_eq!(parse_ast("a(bc)").unwrap().to_regex(), "a(bc)");
+    assert_eq!(parse_ast("a|(b|c)").unwrap().to_regex(), "a|(b|c)");
+    assert_eq!(parse_ast("(ab)*").unwrap().to_regex(), "(ab)*");
+}
+
+#[test]
+fn printer_escapes_operators() {
+    let ast = parse_ast("\\(a\\*\\)\\|\\\\").unwrap();
+    assert_eq!(ast.to_regex(), "\\(a\\*\\)\\|\\\\");
+    let star = Node::with_l('*', Node::with_lr('·', leaf('*'), leaf('|')));
+    assert_eq!(star.to_regex(), "(?:\\*\\|)*");
+}
+
+#[test]
+fn printer_round_trips() {
+    for pattern in [
+        "a",
+        "ab|cd",
+        "a*b|c",
+        "(a|b)*c",
+        "x(a|b*)*y",
+        "a(b(c|d))",
+        "\\**",
+    ] {
+        assert_round_trip(pattern);
+    }
+}
+
+#[test]
+fn printer_round_trips_built_trees() {
+    let ast = Node::with_lr(
+        '·',
+        leaf('a'),
+        Node::with_lr('·', Node::with_lr('|', leaf('b'), leaf('c')), leaf('·')),
+    );
+    assert_eq!(ast.to_regex(), "a(?:(?:b|c)\\·)");
+    assert_eq!(parse_ast(&ast.to_regex()).unwrap(), ast);
+}
+
+#[test]
+fn implicit_concatenation_around_groups() {
+    let parser = crate::RegexParser::from("a(b|c)d");
+    assert_eq!(parser.parse("xacd"), Some(1));
+    assert_eq!(parser.parse("ad"), None);
+}
+
+fn leaf(value: char) -> Node<char> {
+    Node {
+        value,
+        left: None,
+        right: None,
+    }
+}
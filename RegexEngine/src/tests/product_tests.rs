@@ -0,0 +1,111 @@
+use super::RegexParser;
+use crate::automaton::dfa::DFA;
+use std::collections::HashMap;
+
+// =============================================================================
+// PRODUCT CONSTRUCTION TESTS
+// =============================================================================
+
+fn dfa_for(pattern: &str) -> DFA<char> {
+    RegexParser::from(pattern).get_dfa_temp().unwrap()
+}
+
+fn accepts(dfa: &DFA<char>, word: &str) -> bool {
+    dfa.accepts(&word.chars().collect::<Vec<char>>())
+}
+
+#[test]
+fn intersection_accepts_common_strings() {
+    let both = DFA::intersect(&dfa_for("(a|b)*a"), &dfa_for("a(a|b)*"));
+    for word in ["a", "aa", "aba", "abba"] {
+        assert!(accepts(&both, word), "{}", word);
+    }
+    for word in ["", "ab", "ba", "b"] {
+        assert!(!accepts(&both, word), "{}", word);
+    }
+}
+
+#[test]
+fn union_accepts_either_language() {
+    let either = DFA::union(&dfa_for("ab*"), &dfa_for("c"));
+    for word in ["a", "abb", "c"] {
+        assert!(accepts(&either, word), "{}", word);
+    }
+    for word in ["", "ac", "cc", "b"] {
+        assert!(!accepts(&either, word), "{}", word);
+    }
+}
+
+#[test]
+fn difference_removes_the_right_language() {
+    let rest = DFA::difference(&dfa_for("(a|b)*"), &dfa_for("(a|b)*a"));
+    for word in ["", "b", "ab", "aab"] {
+        assert!(accepts(&rest, word), "{}", word);
+    }
+    for word in ["a", "ba", "bba", "c"] {
+        assert!(!accepts(&rest, word), "{}", word);
+    }
+}
+
+#[test]
+fn products_are_minimal() {
+    let pattern = dfa_for("(a|b)*abb");
+    let same = DFA::intersect(&pattern, &pattern);
+    assert_eq!(same.automaton.len(), pattern.automaton.len());
+    // Nothing is left, so only the start remains
+    let nothing = DFA::difference(&pattern, &pattern);
+    assert_eq!(nothing.automaton.len(), 1);
+    assert!(nothing.end.unwrap().is_empty());
+}
+
+#[test]
+fn overlapping_route_patterns_are_detected() {
+    let routes = dfa_for("/api/(u|v)(u|v)*");
+    assert!(DFA::overlaps(&routes, &dfa_for("/api/uu*")));
+    assert!(!DFA::overlaps(&routes, &dfa_for("/api/x")));
+    assert!(!DFA::overlaps(&dfa_for("ab*"), &dfa_for("ba*")));
+}
+
+// Every string starting with an 'a', written with a default transition
+fn starts_with_a() -> DFA<char> {
+    DFA {
+        automaton: HashMap::from([(1, HashMap::from([('a', 2)])), (2, HashMap::new())]),
+        start: Some(1),
+        end: Some(HashMap::from([(2, vec![0])])),
+        others: HashMap::from([(2, 2)]),
+    }
+}
+
+#[test]
+fn products_of_default_transitions_stay_exact() {
+    let operands = [
+        starts_with_a(),
+        starts_with_a().complement(),
+        dfa_for("a").complement(),
+        dfa_for("(a|b)*a").complement(),
+        dfa_for("ab|b"),
+    ];
+    let words = [
+        "", "a", "b", "é", "aa", "ab", "ba", "bé", "aab", "abé", "bba",
+    ];
+    for left in operands.iter() {
+        for right in operands.iter() {
+            let both = DFA::intersect(left, right);
+            let either = DFA::union(left, right);
+            let rest = DFA::difference(left, right);
+            for word in words {
+                let (l, r) = (accepts(left, word), accepts(right, word));
+                assert_eq!(accepts(&both, word), l && r, "{}", word);
+                assert_eq!(accepts(&either, word), l || r, "{}", word);
+                assert_eq!(accepts(&rest, word), l && !r, "{}", word);
+            }
+            if let Some(word) = DFA::shortest_witness(left, right, |l, r| l && !r) {
+                assert!(accepts(left, &word) && !accepts(right, &word), "{}", word);
+            }
+        }
+    }
+    // Nothing starting with an 'a' is left once those strings are removed
+    let everything = DFA::union(&starts_with_a(), &starts_with_a().complement());
+    let rest = DFA::difference(&everything, &starts_with_a());
+    assert!(!DFA::overlaps(&rest, &starts_with_a()));
+}
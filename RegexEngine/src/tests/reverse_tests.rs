@@ -0,0 +1,89 @@
+use super::RegexParser;
+use crate::haystack::{self, Haystack};
+
+// =============================================================================
+// REVERSE SEARCH TESTS
+// =============================================================================
+
+#[test]
+fn reverse_pass_finds_leftmost_start_in_bytes() {
+    let reverse = RegexParser::from("abcd|c").get_reverse().unwrap().clone();
+    // The earliest end belongs to "c", but "abcd" starts further left
+    assert_eq!(reverse.rfind_start("xabcd", 5), Some(1));
+    assert_eq!(reverse.rfind_start("ααc", 5), Some(4));
+    assert_eq!(reverse.rfind_start("xyz", 3), None);
+}
+
+#[test]
+fn reverse_search_agrees_with_forward_scans() {
+    let patterns = ["a", "ab|cd", "a*b|c", "(a|b)*abb", "abcd|c", "a*"];
+    let texts = ["", "a", "xxab", "cababb", "abcabcd", "bbbb", "ßaα"];
+    for pattern in patterns {
+        let parser = RegexParser::from(pattern);
+        let dense = parser.get_dense().unwrap();
+        for text in texts {
+            assert_eq!(
+                parser.parse(text),
+                dense.parse(text),
+                "{} on {:?}",
+                pattern,
+                text
+            );
+        }
+    }
+}
+
+#[test]
+fn byte_haystacks_report_byte_offsets() {
+    let parser = RegexParser::from("ab");
+    assert_eq!(parser.parse_bytes("ααab".as_bytes()), Some(4));
+    assert_eq!(parser.parse("ααab"), Some(2));
+    // Invalid bytes are skipped over like any other character
+    assert_eq!(parser.parse_bytes(b"\xff\xe2\x82ab"), Some(3));
+    assert_eq!(parser.parse_bytes(b"\xe2\x82\xac"), None);
+}
+
+#[test]
+fn backward_decoding_mirrors_forward_decoding() {
+    let inputs: [&[u8]; 4] = [
+        "aß€😀".as_bytes(),
+        b"\xe2\x82",
+        b"\xf0\x9f\x98\x80\x80a",
+        b"\xc0\xafz\xed\xa0\x80",
+    ];
+    for bytes in inputs {
+        let (_, offsets) = haystack::decode(bytes, char::REPLACEMENT_CHARACTER);
+        let mut backward = vec![bytes.len()];
+        let mut offset = bytes.len();
+        while let Some((_, width)) = bytes.char_before(offset) {
+            offset -= width;
+            backward.push(offset);
+        }
+        backward.reverse();
+        assert_eq!(offsets, backward, "{:?}", bytes);
+    }
+}
+
+#[test]
+fn every_engine_accepts_byte_haystacks() {
+    use crate::regex_parser::Engine;
+    for engine in [Engine::Dfa, Engine::Nfa, Engine::LazyDfa] {
+        let parser = RegexParser::with_engine("a*b", engine);
+        assert_eq!(parser.parse_bytes(b"\xffzzab"), Some(3), "{:?}", engine);
+    }
+}
+
+#[test]
+fn invalid_bytes_never_match_a_pattern_character() {
+    use crate::regex_parser::Engine;
+    for engine in [Engine::Auto, Engine::Dfa, Engine::Nfa, Engine::LazyDfa] {
+        let parser = RegexParser::with_engine("a\u{FFFD}", engine);
+        assert_eq!(parser.parse_bytes(b"a\xff"), None, "{:?}", engine);
+        assert_eq!(
+            parser.parse_bytes("xa\u{FFFD}".as_bytes()),
+            Some(1),
+            "{:?}",
+            engine
+        );
+    }
+}
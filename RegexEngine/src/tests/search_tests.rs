@@ -0,0 +1,92 @@
+use super::RegexParser;
+use crate::automaton::dense::{DenseDFA, DEAD};
+use crate::automaton::dfa::DFA;
+use crate::regex_parser::Engine;
+
+// =============================================================================
+// UNANCHORED SEARCH TESTS
+// =============================================================================
+
+fn forward_for(pattern: &str) -> DenseDFA {
+    RegexParser::from(pattern).get_forward().unwrap().clone()
+}
+
+#[test]
+fn forward_pass_stops_at_earliest_match_end() {
+    let forward = forward_for("abc|b");
+    assert_eq!(forward.earliest_end("xabc"), Some(3));
+    assert_eq!(forward.earliest_end("xac"), None);
+    assert_eq!(forward.earliest_end("ααb"), Some(3));
+    assert_eq!(forward_for("a*").earliest_end("bbb"), Some(0));
+}
+
+#[test]
+fn unanchored_automaton_never_dies() {
+    for pattern in ["ab|cd", "a*b", "(a|b)*abb"] {
+        let parser = RegexParser::from(pattern);
+        let forward = parser.get_dense().unwrap().unanchored(usize::MAX).unwrap();
+        for state in 1..forward.state_count() as u32 {
+            for token in ['a', 'b', 'c', 'd', 'z'] {
+                assert_ne!(forward.next_state(state, token), DEAD, "{}", pattern);
+            }
+        }
+    }
+}
+
+#[test]
+fn unanchored_construction_gives_up_past_its_limit() {
+    // Only 18 anchored states, but every start still alive has to be tracked
+    let pattern = "a".to_string() + &"(?:a|b)".repeat(16);
+    let efa = RegexParser::with_engine(&pattern, Engine::Nfa).get_efa_temp();
+    let anchored = DenseDFA::from(&DFA::from_efa(&efa).unwrap());
+    assert!(anchored.state_count() < 20);
+    assert!(anchored.unanchored(1000).is_none());
+    assert!(anchored.unanchored_leftmost(1000).is_none());
+
+    // The lazy DFA searches instead
+    let parser = RegexParser::from(&pattern);
+    assert!(parser.get_forward().is_none());
+    let text = "b".repeat(10) + &"a".repeat(17);
+    assert_eq!(parser.parse(&text), Some(10));
+    assert_eq!(parser.parse(&"a".repeat(16)), None);
+}
+
+#[test]
+fn search_reports_leftmost_start_before_earliest_end() {
+    let parser = RegexParser::from("abc|b");
+    assert_eq!(parser.parse("xabc"), Some(1));
+    assert_eq!(parser.parse("xxb"), Some(2));
+    assert_eq!(parser.parse("xac"), None);
+    assert_eq!(RegexParser::from("(a|b)*abb").parse("cababb"), Some(1));
+}
+
+#[test]
+fn long_haystack_without_match_is_rejected_in_one_pass() {
+    let text = "a".repeat(200_000);
+    assert_eq!(RegexParser::from("a*b").parse(&text), None);
+}
+
+#[test]
+fn late_match_after_long_dead_runs_is_found_in_one_pass() {
+    // Every run started in the prefix survives until the 'c'
+    let text = "a".repeat(200_000) + "cb";
+    assert_eq!(RegexParser::from("a*b").parse(&text), Some(200_001));
+}
+
+#[test]
+fn dfa_walk_does_not_recurse_per_character() {
+    let text = "a".repeat(200_000) + "b";
+    let dfa = RegexParser::from("a*b").get_dfa_temp().unwrap();
+    assert_eq!(dfa.parse(&text), Some(0));
+    assert_eq!(dfa.parse("ccb"), Some(2));
+}
+
+#[test]
+fn forward_pass_stops_once_the_runs_alive_at_the_earliest_end_die() {
+    // "c" ends first, but the run of "abcd" started before it and goes on
+    let forward = forward_for("abcd|c");
+    assert_eq!(forward.leftmost_end("xabcdabcd"), Some(5));
+    assert_eq!(forward.leftmost_end("xabcex"), Some(4));
+    assert_eq!(forward.leftmost_end("xyz"), None);
+    assert_eq!(forward_for("a*").leftmost_end("aab"), Some(2));
+}
@@ -0,0 +1,80 @@
+use super::RegexParser;
+use crate::{ParseError, RegexSet};
+
+// =============================================================================
+// REGEX SET TESTS
+// =============================================================================
+
+#[test]
+fn set_reports_every_matching_pattern() {
+    let set = RegexSet::from(&["ab", "a*c", "(x|y)z", "b"]);
+    assert_eq!(set.len(), 4);
+    assert_eq!(set.matches("xzab"), vec![0, 2, 3]);
+    assert_eq!(set.matches("aaac"), vec![1]);
+    assert_eq!(set.matches("qqq"), Vec::<usize>::new());
+    assert!(set.is_match("yz"));
+    assert!(!set.is_match("qqq"));
+}
+
+#[test]
+fn set_agrees_with_separate_parsers() {
+    let patterns = ["ab|cd", "(a|b)*abb", "c", "a*", "dd(a|b)"];
+    let set = RegexSet::from(&patterns);
+    for text in ["", "abb", "xcdx", "ddb", "ababb", "zzz"] {
+        let expected: Vec<usize> = patterns
+            .iter()
+            .enumerate()
+            .filter(|(_, pattern)| RegexParser::from(pattern).is_match(text))
+            .map(|(id, _)| id)
+            .collect();
+        assert_eq!(set.matches(text), expected, "{:?}", text);
+    }
+}
+
+#[test]
+fn final_states_carry_pattern_ids() {
+    let set = RegexSet::from(&["a*b", "a*b", "a"]);
+    let end = set.get_dfa().unwrap().end.clone().unwrap();
+    let mut labels: Vec<Vec<usize>> = end.into_values().collect();
+    labels.sort();
+    assert_eq!(labels, vec![vec![0, 1], vec![2]]);
+}
+
+#[test]
+fn minimization_keeps_patterns_apart() {
+    // As a single pattern "a*b|cb" needs 4 states, but the two ends differ here
+    let set = RegexSet::from(&["a*b", "cb"]);
+    assert_eq!(set.get_dfa().unwrap().automaton.len(), 5);
+    let single = RegexParser::from("a*b|cb").get_dfa_temp().unwrap();
+    assert_eq!(single.automaton.len(), 4);
+}
+
+#[test]
+fn empty_set_matches_nothing() {
+    let set = RegexSet::from(&[]);
+    assert!(set.is_empty());
+    assert!(set.matches("abc").is_empty());
+    assert!(!set.is_match(""));
+}
+
+#[test]
+fn malformed_patterns_are_reported() {
+    assert_eq!(
+        RegexSet::new(&["ab", "a(b"]).err(),
+        Some(ParseError::UnbalancedParenthesis)
+    );
+    assert!(RegexSet::new(&["ab", "a*"]).is_ok());
+}
+
+#[test]
+fn oversized_unanchored_set_runs_every_start() {
+    // Every start still alive has to be tracked, far more than the limit allows
+    let long = "a".to_string() + &"(a|b)".repeat(16);
+    let set = RegexSet::from(&[long.as_str(), "c"]);
+    let text = "b".repeat(5) + &"a".repeat(17);
+    assert_eq!(set.matches(&text), vec![0]);
+    assert_eq!(set.matches(&(text.clone() + "c")), vec![0, 1]);
+    assert_eq!(set.matches("bbbc"), vec![1]);
+    assert!(set.is_match(&text));
+    assert!(!set.is_match("bbbb"));
+}
@@ -0,0 +1,80 @@
+use super::RegexParser;
+use crate::regex_parser::parse_ast;
+use crate::thompson::{Inst, Program};
+
+// =============================================================================
+// THOMPSON PROGRAM TESTS
+// =============================================================================
+
+#[test]
+fn program_for_concatenation() {
+    let program = Program::compile(&parse_ast("ab").unwrap());
+    assert_eq!(
+        program.insts,
+        vec![
+            Inst::Save(0, 1),
+            Inst::Char('a', 2),
+            Inst::Char('b', 3),
+            Inst::Save(1, 4),
+            Inst::Match,
+        ]
+    );
+    assert_eq!(program.start, 0);
+    assert_eq!(program.groups(), 1);
+}
+
+#[test]
+fn program_for_star_is_greedy() {
+    let program = Program::compile(&parse_ast("a*").unwrap());
+    assert_eq!(
+        program.insts,
+        vec![
+            Inst::Save(0, 1),
+            Inst::Split(2, 3),
+            Inst::Char('a', 1),
+            Inst::Save(1, 4),
+            Inst::Match,
+        ]
+    );
+}
+
+#[test]
+fn program_numbers_groups_by_opening_parenthesis() {
+    let program = Program::compile(&parse_ast("((a)|(b))(?:c)").unwrap());
+    assert_eq!(program.groups(), 4);
+    let saves: Vec<usize> = program
+        .insts
+        .iter()
+        .filter_map(|inst| match inst {
+            Inst::Save(slot, _) => Some(*slot),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(saves, vec![0, 2, 4, 5, 6, 7, 3, 1]);
+}
+
+#[test]
+fn program_lowers_to_efa() {
+    let program = Program::compile(&parse_ast("a|b").unwrap());
+    let efa = program.to_efa();
+    assert_eq!(efa.get_start(), Some(0));
+    assert_eq!(efa.get_end(), Some(program.insts.len() as u32 - 1));
+    assert_eq!(efa.parse("xb"), Some(1));
+    assert_eq!(efa.parse("xy"), None);
+}
+
+#[test]
+fn groups_do_not_change_matching() {
+    let parser = RegexParser::from("(a(b))*c|(?:d)");
+    assert_eq!(parser.parse("ababc"), Some(0));
+    assert_eq!(parser.parse("xxd"), Some(2));
+    assert_eq!(parser.parse("aab"), None);
+    assert_eq!(parser.get_program().groups(), 3);
+}
+
+#[test]
+fn printer_keeps_capture_groups() {
+    assert_eq!(parse_ast("(a)|((b))").unwrap().to_regex(), "(a)|((b))");
+    assert_eq!(parse_ast("(?:a|b)*(?:c)").unwrap().to_regex(), "(?:a|b)*c");
+    assert_eq!(parse_ast("\\(?:").unwrap().to_regex(), "\\(\\?:");
+}
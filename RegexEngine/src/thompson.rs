@@ -0,0 +1,190 @@
+use crate::automaton::efa::EFA;
+use crate::tree::Node;
+use crate::type_defs::State;
+
+pub type InstPtr = usize;
+
+/// Capture slots filled by a match, as character positions. See `Program`.
+pub type Slots = Vec<Option<usize>>;
+
+// Placeholder target of an instruction whose successor is not compiled yet
+const HOLE: InstPtr = InstPtr::MAX;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Inst {
+    /// Consumes the given character and continues at the next instruction
+    Char(char, InstPtr),
+    /// Continues at both instructions, the first one having priority
+    Split(InstPtr, InstPtr),
+    /// Records the current position in a capture slot
+    Save(usize, InstPtr),
+    Match,
+}
+
+/// A Thompson NFA compiled from the syntax tree as a list of instructions.
+///
+/// Capture group `i` writes its bounds into slots `2 * i` and `2 * i + 1`,
+/// group 0 being the whole match. Groups are numbered by their opening parenthesis.
+#[derive(Debug, Clone)]
+pub struct Program {
+    pub insts: Vec<Inst>,
+    pub start: InstPtr,
+    pub slots: usize,
+}
+
+struct Fragment {
+    start: InstPtr,
+    // Instructions whose successor still has to be patched
+    holes: Vec<InstPtr>,
+}
+
+impl Program {
+    pub fn compile(tree: &Node<char>) -> Self {
+        let mut program = Program {
+            insts: Vec::new(),
+            start: 0,
+            slots: 2,
+        };
+
+        let save_start = program.push(Inst::Save(0, HOLE));
+        let body = program.compile_node(tree);
+        program.patch(&[save_start], body.start);
+        let save_end = program.push(Inst::Save(1, HOLE));
+        program.patch(&body.holes, save_end);
+        let accept = program.push(Inst::Match);
+        program.patch(&[save_end], accept);
+
+        program.start = save_start;
+        program
+    }
+
+    fn push(&mut self, inst: Inst) -> InstPtr {
+        self.insts.push(inst);
+        self.insts.len() - 1
+    }
+
+    fn patch(&mut self, holes: &[InstPtr], target: InstPtr) {
+        for hole in holes {
+            match &mut self.insts[*hole] {
+                Inst::Char(_, next) | Inst::Save(_, next) => *next = target,
+                // Only the exit branch of a loop is left open, or both
+                // branches of an empty string
+                Inst::Split(first, next) => {
+                    if *first == HOLE {
+                        *first = target;
+                    }
+                    *next = target;
+                }
+                Inst::Match => panic!("Match instructions have no successor"),
+            }
+        }
+    }
+
+    fn compile_node(&mut self, node: &Node<char>) -> Fragment {
+        if node.is_epsilon() {
+            // Nothing is consumed: both branches lead to what follows
+            let pc = self.push(Inst::Split(HOLE, HOLE));
+            return Fragment {
+                start: pc,
+                holes: vec![pc],
+            };
+        }
+        if node.is_leaf() {
+            let pc = self.push(Inst::Char(node.value, HOLE));
+            return Fragment {
+                start: pc,
+                holes: vec![pc],
+            };
+        }
+
+        match node.value {
+            '·' => {
+                let left = self.compile_node(node.left.as_ref().unwrap());
+                let right = self.compile_node(node.right.as_ref().unwrap());
+                self.patch(&left.holes, right.start);
+                Fragment {
+                    start: left.start,
+                    holes: right.holes,
+                }
+            }
+            '|' => {
+                let split = self.push(Inst::Split(HOLE, HOLE));
+                let left = self.compile_node(node.left.as_ref().unwrap());
+                let right = self.compile_node(node.right.as_ref().unwrap());
+                self.insts[split] = Inst::Split(left.start, right.start);
+                let mut holes = left.holes;
+                holes.extend(right.holes);
+                Fragment {
+                    start: split,
+                    holes,
+                }
+            }
+            '*' => {
+                let split = self.push(Inst::Split(HOLE, HOLE));
+                let body = self.compile_node(node.left.as_ref().unwrap());
+                // Greedy: staying in the loop is preferred over leaving it
+                self.insts[split] = Inst::Split(body.start, HOLE);
+                self.patch(&body.holes, split);
+                Fragment {
+                    start: split,
+                    holes: vec![split],
+                }
+            }
+            '(' => {
+                let group = self.slots / 2;
+                self.slots += 2;
+                let open = self.push(Inst::Save(2 * group, HOLE));
+                let body = self.compile_node(node.left.as_ref().unwrap());
+                self.patch(&[open], body.start);
+                let close = self.push(Inst::Save(2 * group + 1, HOLE));
+                self.patch(&body.holes, close);
+                Fragment {
+                    start: open,
+                    holes: vec![close],
+                }
+            }
+            _ => panic!("Unknown operator {}", node.value),
+        }
+    }
+
+    /// Number of capture groups, including the implicit group 0.
+    pub fn groups(&self) -> usize {
+        self.slots / 2
+    }
+
+    /// Lowers the program into an epsilon automaton whose states are the
+    /// instruction pointers. `Split` and `Save` both become epsilon transitions.
+    pub fn to_efa(&self) -> EFA<char> {
+        let mut efa: EFA<char> = EFA::new();
+        for (pc, inst) in self.insts.iter().enumerate() {
+            let state = pc as State;
+            match *inst {
+                Inst::Char(ch, next) => efa.transition(state, Some(ch), next as State),
+                Inst::Split(first, second) => {
+                    efa.transition(state, None, first as State);
+                    efa.transition(state, None, second as State);
+                }
+                Inst::Save(_, next) => efa.transition(state, None, next as State),
+                Inst::Match => {
+                    efa.empty_transition(state);
+                    efa.set_end(state);
+                }
+            }
+        }
+        efa.set_start(self.start as State);
+        efa
+    }
+
+    pub fn print(&self) {
+        println!("Program - Start {} - Slots {}", self.start, self.slots);
+        for (pc, inst) in self.insts.iter().enumerate() {
+            let description = match inst {
+                Inst::Char(ch, next) => format!("char {:?} -> {}", ch, next),
+                Inst::Split(first, second) => format!("split {}, {}", first, second),
+                Inst::Save(slot, next) => format!("save {} -> {}", slot, next),
+                Inst::Match => "match".to_string(),
+            };
+            println!(" {:>4} | {}", pc, description);
+        }
+    }
+}
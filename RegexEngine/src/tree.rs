@@ -1,4 +1,6 @@
-#[derive(Debug, Clone)]
+use crate::type_defs::EPS;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Node<T> {
     pub value: T,
     pub left: Option<Box<Node<T>>>,
@@ -15,6 +17,10 @@ impl<T: Clone> Node<T> {
         }
         result.push(self.value.clone());
     }
+
+    pub fn is_leaf(&self) -> bool {
+        self.left.is_none() && self.right.is_none()
+    }
 }
 
 impl Node<char> {
@@ -32,4 +38,24 @@ impl Node<char> {
             right: None,
         };
     }
+    pub fn leaf(value: char) -> Node<char> {
+        return Node {
+            value,
+            left: None,
+            right: None,
+        };
+    }
+    /// The empty string, written `(?:)` in patterns. Every leaf is a
+    /// character, `ε` included, so the empty string is instead the only node
+    /// with a right child and no left one.
+    pub fn epsilon() -> Node<char> {
+        return Node {
+            value: EPS,
+            left: None,
+            right: Some(Box::new(Node::leaf(EPS))),
+        };
+    }
+    pub fn is_epsilon(&self) -> bool {
+        self.value == EPS && self.left.is_none() && self.right.is_some()
+    }
 }
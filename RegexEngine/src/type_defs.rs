@@ -1,3 +1,6 @@
-pub type State = u8;
+pub type State = u32;
+
+/// Index of a pattern in a set, carried by the final states that accept it
+pub type PatternId = usize;
 
 pub const EPS: char = 'ε';